@@ -0,0 +1,39 @@
+use std::{collections::BTreeMap, env, fs, path::Path};
+
+use serde_yaml::Value;
+
+#[path = "src/localization/template.rs"]
+mod template;
+
+/// The translations the keys are generated from
+const DEFAULT_TRANSLATIONS: &str = "lang/en_US.yaml";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", DEFAULT_TRANSLATIONS);
+
+    // Read the default translations
+    let content = fs::read_to_string(DEFAULT_TRANSLATIONS).expect("Could not read translations");
+    let translations =
+        serde_yaml::from_str::<Value>(&content).expect("Could not parse translations");
+
+    // Collect the keys
+    let mut keys = BTreeMap::new();
+    template::flatten(&translations, "", &mut keys);
+
+    // Generate the table of keys
+    let mut generated = String::from("&[\n");
+    for (key, names) in keys {
+        let names = names
+            .iter()
+            .map(|name| format!("{:?}", name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        generated.push_str(&format!("    ({:?}, &[{}]),\n", key, names));
+    }
+    generated.push(']');
+
+    // Write the table
+    let out_dir = env::var_os("OUT_DIR").expect("OUT_DIR is not set");
+    fs::write(Path::new(&out_dir).join("translation_keys.rs"), generated)
+        .expect("Could not write translation keys");
+}
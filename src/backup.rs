@@ -0,0 +1,398 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use anyhow::{Context as _, anyhow};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait as _, DatabaseBackend, DbConn, EntityTrait,
+    PaginatorTrait as _, TransactionTrait as _, Value,
+};
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, GuildId, MessageId, UserId};
+
+use crate::{
+    db::{channel_settings, guild_settings, quotebook, user_settings},
+    repository::{Quote, QuoteRepository, SettingsRepository},
+    settings::{SETTINGS, SettingTarget, get_setting},
+    utils::snowflake_as_string,
+};
+
+/// The version of the backup format, which changes when old backups can no longer be restored
+pub(crate) const BACKUP_VERSION: u32 = 1;
+
+/// The place a group of settings is set in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "scope", rename_all = "snake_case")]
+pub(crate) enum BackupTarget {
+    User {
+        #[serde(with = "snowflake_as_string")]
+        user_id: UserId,
+    },
+    Channel {
+        #[serde(with = "snowflake_as_string")]
+        guild_id: GuildId,
+        #[serde(with = "snowflake_as_string")]
+        channel_id: ChannelId,
+    },
+    Guild {
+        #[serde(with = "snowflake_as_string")]
+        guild_id: GuildId,
+    },
+}
+
+impl From<SettingTarget> for BackupTarget {
+    fn from(target: SettingTarget) -> Self {
+        match target {
+            SettingTarget::User(user_id) => Self::User { user_id },
+            SettingTarget::Channel(guild_id, channel_id) => Self::Channel {
+                guild_id,
+                channel_id,
+            },
+            SettingTarget::Guild(guild_id) => Self::Guild { guild_id },
+        }
+    }
+}
+
+impl From<BackupTarget> for SettingTarget {
+    fn from(target: BackupTarget) -> Self {
+        match target {
+            BackupTarget::User { user_id } => Self::User(user_id),
+            BackupTarget::Channel {
+                guild_id,
+                channel_id,
+            } => Self::Channel(guild_id, channel_id),
+            BackupTarget::Guild { guild_id } => Self::Guild(guild_id),
+        }
+    }
+}
+
+/// The settings set for a target in a backup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SettingsBackup {
+    #[serde(flatten)]
+    pub target: BackupTarget,
+    /// The values of the settings, by key
+    pub values: BTreeMap<String, String>,
+}
+
+/// A quote in a backup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct QuoteBackup {
+    /// The number users know the quote by
+    pub id: i64,
+    #[serde(with = "snowflake_as_string")]
+    pub message_id: MessageId,
+    #[serde(with = "snowflake_as_string")]
+    pub guild_id: GuildId,
+    #[serde(with = "snowflake_as_string")]
+    pub author_id: UserId,
+    pub datetime: String,
+}
+
+impl From<Quote> for QuoteBackup {
+    fn from(quote: Quote) -> Self {
+        Self {
+            id: quote.id,
+            message_id: quote.message_id.0,
+            guild_id: quote.guild_id.0,
+            author_id: quote.author_id.0,
+            datetime: quote.datetime.and_utc().to_rfc3339(),
+        }
+    }
+}
+
+/// Everything the bot stores, in a form that does not depend on the database backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Backup {
+    pub version: u32,
+    pub created_at: String,
+    pub settings: Vec<SettingsBackup>,
+    /// The quotes, in the order they were saved
+    pub quotes: Vec<QuoteBackup>,
+}
+
+impl Backup {
+    /// Write the backup as JSON
+    pub(crate) fn to_json(&self) -> Result<Vec<u8>, anyhow::Error> {
+        serde_json::to_vec_pretty(self).context("Failed to write backup as JSON")
+    }
+
+    /// Read a backup from JSON
+    pub(crate) fn from_json(json: &[u8]) -> Result<Self, anyhow::Error> {
+        let backup = serde_json::from_slice::<Self>(json).context("Failed to read backup JSON")?;
+        if backup.version != BACKUP_VERSION {
+            return Err(anyhow!(
+                "Backup version {} is not supported, expected {}",
+                backup.version,
+                BACKUP_VERSION
+            ));
+        }
+        Ok(backup)
+    }
+}
+
+/// Collect everything stored in the repositories
+pub(crate) async fn create_backup(
+    quotes: &dyn QuoteRepository,
+    settings: &dyn SettingsRepository,
+) -> Result<Backup, anyhow::Error> {
+    // Get the settings of every target
+    let mut all_settings = settings
+        .get_all_settings()
+        .await
+        .context("Could not get settings")?;
+    all_settings.sort_by_key(|(target, _)| *target);
+    let settings = all_settings
+        .into_iter()
+        .map(|(target, values)| SettingsBackup {
+            target: target.into(),
+            values: SETTINGS
+                .iter()
+                .filter_map(|setting| {
+                    values
+                        .get(setting)
+                        .map(|value| (setting.key.to_owned(), value.to_owned()))
+                })
+                .collect(),
+        })
+        .filter(|backup| !backup.values.is_empty())
+        .collect();
+
+    // Get every quote
+    let quotes = quotes
+        .get_all_quotes()
+        .await
+        .context("Could not get quotes")?
+        .into_iter()
+        .map(QuoteBackup::from)
+        .collect();
+
+    Ok(Backup {
+        version: BACKUP_VERSION,
+        created_at: Utc::now().to_rfc3339(),
+        settings,
+        quotes,
+    })
+}
+
+/// Set the columns of the settings in a backup entry on a database row
+fn set_setting_columns<A>(
+    row: &mut A,
+    entry: &SettingsBackup,
+    target: SettingTarget,
+) -> Result<(), anyhow::Error>
+where
+    A: ActiveModelTrait,
+    <A::Entity as EntityTrait>::Column: FromStr,
+{
+    for (key, value) in &entry.values {
+        let setting = get_setting(key).ok_or(anyhow!("Unknown setting {}", key))?;
+        if !setting.has_scope(target.scope()) {
+            return Err(anyhow!("Setting {} can not be set for {:?}", key, target));
+        }
+        setting
+            .validate(value)
+            .with_context(|| format!("Invalid value for setting {} of {:?}", key, target))?;
+        let column = setting
+            .column
+            .parse()
+            .map_err(|_| anyhow!("Unknown settings column {}", setting.column))?;
+        row.set(column, Value::String(Some(Box::new(value.to_owned()))));
+    }
+    Ok(())
+}
+
+/// Load a backup into a database that has nothing stored
+///
+/// The backup is loaded in one transaction, so a backup that fails to load leaves the database
+/// empty.
+pub(crate) async fn restore_backup(db: &DbConn, backup: &Backup) -> Result<(), anyhow::Error> {
+    // Use the active value types
+    use sea_orm::ActiveValue::*;
+
+    let txn = db.begin().await.context("Could not start transaction")?;
+
+    // Refuse to mix the backup with existing data
+    let rows = quotebook::Entity::find().count(&txn).await?
+        + user_settings::Entity::find().count(&txn).await?
+        + channel_settings::Entity::find().count(&txn).await?
+        + guild_settings::Entity::find().count(&txn).await?;
+    if rows > 0 {
+        return Err(anyhow!(
+            "The database already has data, backups can only be restored into an empty one"
+        ));
+    }
+
+    // Restore the settings
+    for entry in &backup.settings {
+        let target = SettingTarget::from(entry.target.clone());
+        match target {
+            SettingTarget::User(user_id) => {
+                let mut row = user_settings::ActiveModel {
+                    user_id: Set(user_id.into()),
+                    ..Default::default()
+                };
+                set_setting_columns(&mut row, entry, target)?;
+                row.insert(&txn)
+                    .await
+                    .context("Could not restore user settings")?;
+            }
+            SettingTarget::Channel(guild_id, channel_id) => {
+                let mut row = channel_settings::ActiveModel {
+                    channel_id: Set(channel_id.into()),
+                    guild_id: Set(guild_id.into()),
+                    ..Default::default()
+                };
+                set_setting_columns(&mut row, entry, target)?;
+                row.insert(&txn)
+                    .await
+                    .context("Could not restore channel settings")?;
+            }
+            SettingTarget::Guild(guild_id) => {
+                let mut row = guild_settings::ActiveModel {
+                    guild_id: Set(guild_id.into()),
+                    ..Default::default()
+                };
+                set_setting_columns(&mut row, entry, target)?;
+                row.insert(&txn)
+                    .await
+                    .context("Could not restore guild settings")?;
+            }
+        }
+    }
+
+    // Restore the quotes with their ids, which users know them by
+    for quote in &backup.quotes {
+        let datetime = DateTime::parse_from_rfc3339(&quote.datetime)
+            .context("Invalid quote datetime")?
+            .naive_utc();
+        quotebook::ActiveModel {
+            id: Set(quote.id),
+            message_id: Set(quote.message_id.into()),
+            guild_id: Set(quote.guild_id.into()),
+            author_id: Set(quote.author_id.into()),
+            datetime: Set(datetime),
+        }
+        .insert(&txn)
+        .await
+        .context("Could not restore quote")?;
+    }
+
+    // Postgres sequences do not move past ids that were inserted explicitly
+    if txn.get_database_backend() == DatabaseBackend::Postgres {
+        for table in [
+            "quotebook",
+            "user_settings",
+            "channel_settings",
+            "guild_settings",
+        ] {
+            txn.execute_unprepared(&format!(
+                r#"SELECT setval(pg_get_serial_sequence('"{table}"', 'id'), COALESCE(MAX("id"), 0) + 1, false) FROM "{table}""#
+            ))
+            .await
+            .context("Could not move id sequence")?;
+        }
+    }
+
+    txn.commit()
+        .await
+        .context("Could not commit restored backup")
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::Database;
+    use sea_orm_migration::MigratorTrait as _;
+
+    use crate::{
+        db::Migrator,
+        repository::{DatabaseRepository, MemoryRepository},
+        settings::{LANGUAGE, TIMEZONE},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_backup_round_trip() {
+        let source = MemoryRepository::default();
+        let guild_id = GuildId::new(3);
+        source
+            .set_setting(
+                SettingTarget::Channel(guild_id, ChannelId::new(4)),
+                &LANGUAGE,
+                Some("es-419".to_owned()),
+            )
+            .await
+            .unwrap();
+        source
+            .set_setting(
+                SettingTarget::User(UserId::new(1)),
+                &TIMEZONE,
+                Some("Europe/Madrid".to_owned()),
+            )
+            .await
+            .unwrap();
+        source
+            .add_quote(
+                MessageId::new(1235772100384526377),
+                guild_id,
+                UserId::new(1),
+                DateTime::UNIX_EPOCH.naive_utc(),
+            )
+            .await
+            .unwrap();
+
+        // Ids are written as strings
+        let mut backup = create_backup(&source, &source).await.unwrap();
+        let json = backup.to_json().unwrap();
+        let value = serde_json::from_slice::<serde_json::Value>(&json).unwrap();
+        assert_eq!(value["quotes"][0]["message_id"], "1235772100384526377");
+        assert_eq!(value["settings"][0]["scope"], "user");
+        assert_eq!(value["settings"][1]["channel_id"], "4");
+        assert_eq!(Backup::from_json(&json).unwrap(), backup);
+
+        // A backup that fails partway leaves the database empty
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        Migrator::up(&db, None).await.unwrap();
+        let mut duplicate = backup.quotes[0].clone();
+        duplicate.id = 2;
+        backup.quotes.push(duplicate);
+        assert!(restore_backup(&db, &backup).await.is_err());
+        let repository = DatabaseRepository::new(db.clone());
+        let empty = create_backup(&repository, &repository).await.unwrap();
+        assert!(empty.settings.is_empty() && empty.quotes.is_empty());
+
+        // Invalid settings are not restored either
+        backup.quotes.pop();
+        let mut invalid = backup.clone();
+        invalid.settings[0]
+            .values
+            .insert(TIMEZONE.key.to_owned(), "Not/A_Timezone".to_owned());
+        let err = restore_backup(&db, &invalid).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("timezone"));
+        let empty = create_backup(&repository, &repository).await.unwrap();
+        assert!(empty.settings.is_empty() && empty.quotes.is_empty());
+
+        // The quotes keep their ids, even with gaps
+        backup.quotes[0].id = 7;
+        restore_backup(&db, &backup).await.unwrap();
+        let restored = create_backup(&repository, &repository).await.unwrap();
+        assert_eq!(restored.settings, backup.settings);
+        assert_eq!(restored.quotes, backup.quotes);
+
+        // New quotes are numbered after the restored ones
+        repository
+            .add_quote(
+                MessageId::new(5),
+                guild_id,
+                UserId::new(1),
+                DateTime::UNIX_EPOCH.naive_utc(),
+            )
+            .await
+            .unwrap();
+        let quotes = repository.get_all_quotes().await.unwrap();
+        assert!(quotes[1].id > 7);
+
+        // Restoring into a database with data fails
+        assert!(restore_backup(&db, &backup).await.is_err());
+    }
+}
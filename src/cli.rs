@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use serenity::all::GuildId;
+
+/// Alpha Wolf Bot, a cool Discord bot
+#[derive(Debug, Parser)]
+#[command(version)]
+pub(crate) struct Cli {
+    /// The config file to use instead of `CONFIG_PATH` or config.yaml
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum CliCommand {
+    /// Start the bot, which is what happens when no command is given
+    Run,
+    /// Run or inspect the database migrations
+    Migrate {
+        #[command(subcommand)]
+        action: Option<MigrateAction>,
+    },
+    /// Register the slash commands with Discord, where the config decides if nothing is given
+    RegisterCommands {
+        /// A guild to register the commands in, which can be given more than once
+        #[arg(long = "guild", value_name = "ID")]
+        guilds: Vec<GuildId>,
+        /// Register the commands globally
+        #[arg(long)]
+        global: bool,
+        /// Remove the registered commands instead
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Check the translations files for missing and unused keys
+    CheckTranslations {
+        /// The source directory to check the used keys in, which is `src` if it exists
+        #[arg(long, value_name = "DIR")]
+        sources: Option<PathBuf>,
+    },
+    /// Write a backup of everything the bot stores, to standard output if no file is given
+    Export { file: Option<PathBuf> },
+    /// Load a backup into an empty database
+    Restore { file: PathBuf },
+}
+
+#[derive(Debug, Clone, Copy, Default, Subcommand)]
+pub(crate) enum MigrateAction {
+    /// List the applied and pending migrations
+    #[default]
+    Status,
+    /// Apply pending migrations, all of them if no number is given
+    Up { steps: Option<u32> },
+    /// Roll back applied migrations, one if no number is given
+    Down { steps: Option<u32> },
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory as _;
+
+    use super::*;
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+
+        let cli = Cli::try_parse_from(["awb"]).unwrap();
+        assert!(cli.command.is_none());
+        let cli = Cli::try_parse_from([
+            "awb",
+            "register-commands",
+            "--guild",
+            "1235772100384526377",
+            "--guild",
+            "2",
+            "--config",
+            "awb.yaml",
+        ])
+        .unwrap();
+        assert_eq!(cli.config, Some(PathBuf::from("awb.yaml")));
+        let Some(CliCommand::RegisterCommands {
+            guilds,
+            global,
+            clear,
+        }) = cli.command
+        else {
+            panic!("Expected register-commands");
+        };
+        assert_eq!(
+            guilds,
+            vec![GuildId::new(1235772100384526377), GuildId::new(2)]
+        );
+        assert!(!global && !clear);
+        let cli = Cli::try_parse_from(["awb", "migrate", "down", "2"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(CliCommand::Migrate {
+                action: Some(MigrateAction::Down { steps: Some(2) })
+            })
+        ));
+        let cli =
+            Cli::try_parse_from(["awb", "check-translations", "--sources", "awb/src"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(CliCommand::CheckTranslations { sources: Some(_) })
+        ));
+        assert!(Cli::try_parse_from(["awb", "restore"]).is_err());
+    }
+}
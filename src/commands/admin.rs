@@ -0,0 +1,110 @@
+use anyhow::Context as _;
+use chrono::Utc;
+use poise::{Command, CreateReply, command};
+use serenity::all::CreateAttachment;
+
+use crate::{backup, localization, localize_message, settings::get_context_settings};
+
+use super::{Context, Data, Error, Result};
+
+pub(super) fn get_all_commands() -> Vec<Command<Data, Error>> {
+    vec![admin()]
+}
+
+#[command(
+    slash_command,
+    owners_only,
+    default_member_permissions = "ADMINISTRATOR",
+    subcommands("backup", "reload_translations")
+)]
+pub(super) async fn admin(_ctx: Context<'_>) -> Result {
+    unreachable!();
+}
+
+/// Get a backup of everything the bot stores, which can be restored into any database backend
+#[command(slash_command, owners_only)]
+pub(super) async fn backup(ctx: Context<'_>) -> Result {
+    // Get the context settings
+    let context_settings = get_context_settings(&ctx)
+        .await
+        .context("Failed to get context settings")?;
+
+    // Defer the response
+    ctx.defer_ephemeral()
+        .await
+        .context("Failed to defer response")?;
+
+    // Create the backup
+    let backup = backup::create_backup(ctx.data().quotes.as_ref(), ctx.data().settings.as_ref())
+        .await
+        .context("Failed to create backup")?;
+    let attachment = CreateAttachment::bytes(
+        backup.to_json()?,
+        format!("awb-backup-{}.json", Utc::now().format("%Y%m%dT%H%M%SZ")),
+    );
+
+    // Send the backup
+    ctx.send(
+        CreateReply::default()
+            .content(
+                localize_message!(
+                    "command.admin.backup.response",
+                    &context_settings.language,
+                    settings = backup.settings.len(),
+                    count = backup.quotes.len()
+                )
+                .await
+                .context("Failed to localize message")?,
+            )
+            .attachment(attachment)
+            .ephemeral(true),
+    )
+    .await
+    .context("Failed to send response")?;
+
+    // Return ok
+    Ok(())
+}
+
+/// Reload the translations files, which does not change the registered command names
+#[command(slash_command, owners_only, rename = "reload-translations")]
+pub(super) async fn reload_translations(ctx: Context<'_>) -> Result {
+    // Reload the translations
+    let result = localization::reload_translations();
+
+    // Get the context settings, after reloading in case the language changed
+    let context_settings = get_context_settings(&ctx)
+        .await
+        .context("Failed to get context settings")?;
+
+    // Create the response
+    let response = match result {
+        Ok(language_count) => localize_message!(
+            "command.admin.reload_translations.response.success",
+            &context_settings.language,
+            count = language_count
+        )
+        .await
+        .context("Failed to localize message")?,
+        Err(err) => localize_message!(
+            "command.admin.reload_translations.response.error",
+            &context_settings.language,
+            errors = err
+                .errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+        .await
+        .context("Failed to localize message")?,
+    };
+
+    // Send the response
+    ctx.send(CreateReply::default().content(response).ephemeral(true))
+        .await
+        .context("Failed to send response")?;
+
+    // Return ok
+    Ok(())
+}
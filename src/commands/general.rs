@@ -1,8 +1,13 @@
 use anyhow::{Context as _, anyhow};
+use chrono::Utc;
 use poise::{Command, command};
 
-use crate::localize_message;
 use crate::settings::get_context_settings;
+use crate::{
+    START_TIME,
+    localization::{Argument, TimestampStyle},
+    localize_message,
+};
 
 use super::{Context, Data, Error, Result};
 
@@ -10,22 +15,16 @@ pub(super) fn get_all_commands() -> Vec<Command<Data, Error>> {
     vec![hello(), whereareyou(), whoareyou()]
 }
 
-#[command(
-    slash_command,
-    name_localized("en-US", "hello"),
-    name_localized("es-419", "hola"),
-    description_localized("en-US", "Give a simple greeting"),
-    description_localized("es-419", "Dar un saludo sencillo")
-)]
+#[command(slash_command)]
 pub(super) async fn hello(ctx: Context<'_>) -> Result {
-    let context_settings = get_context_settings(&ctx, &ctx.data().db)
+    let context_settings = get_context_settings(&ctx)
         .await
         .context("Failed to get context settings")?;
     ctx.say(
         localize_message!(
             "command.hello.response",
             &context_settings.language,
-            ctx.author().display_name()
+            user = ctx.author().display_name()
         )
         .await
         .context("Failed to localize message")?,
@@ -37,16 +36,10 @@ pub(super) async fn hello(ctx: Context<'_>) -> Result {
     Ok(())
 }
 
-#[command(
-    slash_command,
-    name_localized("en-US", "whereareyou"),
-    name_localized("es-419", "dondeestas"),
-    description_localized("en-US", "Say where I am"),
-    description_localized("es-419", "Decir dónde estoy")
-)]
+#[command(slash_command)]
 pub(super) async fn whereareyou(ctx: Context<'_>) -> Result {
     // Get the context settings
-    let context_settings = get_context_settings(&ctx, &ctx.data().db)
+    let context_settings = get_context_settings(&ctx)
         .await
         .context("Failed to get context settings")?;
 
@@ -61,8 +54,8 @@ pub(super) async fn whereareyou(ctx: Context<'_>) -> Result {
             localize_message!(
                 "command.whereareyou.response.server",
                 &context_settings.language,
-                guild_name,
-                guild_channel.name
+                guild = guild_name,
+                channel = guild_channel.name
             )
             .await
             .context("Could not localize message")?,
@@ -86,25 +79,24 @@ pub(super) async fn whereareyou(ctx: Context<'_>) -> Result {
     Ok(())
 }
 
-#[command(
-    slash_command,
-    name_localized("en-US", "whoareyou"),
-    name_localized("es-419", "quienestas"),
-    description_localized("en-US", "Say who I am and some info about me"),
-    description_localized("es-419", "Decir quién soy y algunos datos sobre mí")
-)]
+#[command(slash_command)]
 pub(super) async fn whoareyou(ctx: Context<'_>) -> Result {
     // Get the context settings
-    let context_settings = get_context_settings(&ctx, &ctx.data().db)
+    let context_settings = get_context_settings(&ctx)
         .await
         .context("Failed to get context settings")?;
 
+    // Get when the bot was started
+    let started = *START_TIME.get().unwrap_or(&Utc::now());
+
     // Say some info
     ctx.say(
         localize_message!(
             "command.whoareyou.response",
             &context_settings.language,
-            env!("CARGO_PKG_VERSION")
+            version = env!("CARGO_PKG_VERSION"),
+            uptime = Argument::Duration(Utc::now() - started),
+            started = Argument::Timestamp(started, TimestampStyle::ShortDateTime)
         )
         .await
         .context("Failed to localize message")?,
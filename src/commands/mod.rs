@@ -1,8 +1,19 @@
+use std::sync::Arc;
+
 use dashmap::DashMap;
 use sea_orm::DatabaseConnection;
 use serenity::all::{MessageId, UserId};
 
+use crate::{
+    localization::localize_commands,
+    repository::{DatabaseRepository, QuoteRepository, SettingsRepository},
+    settings::SettingsCache,
+    shutdown::Shutdown,
+};
+
+mod admin;
 mod general;
+mod privacy;
 mod purge;
 mod quote;
 mod settings;
@@ -19,25 +30,50 @@ pub(crate) type Context<'a> = poise::Context<'a, Data, Error>;
 /// The data for commands
 #[derive(Debug, Clone)]
 pub(crate) struct Data {
-    pub(crate) db: DatabaseConnection,
+    pub(crate) quotes: Arc<dyn QuoteRepository>,
+    pub(crate) settings: Arc<dyn SettingsRepository>,
+    pub(crate) settings_cache: Arc<SettingsCache>,
+    pub(crate) shutdown: Arc<Shutdown>,
     pub(self) menu_selections: DashMap<(MessageId, UserId), String>,
 }
 
 impl Data {
-    pub(crate) fn new(db: DatabaseConnection) -> Self {
+    pub(crate) fn new(db: DatabaseConnection, shutdown: Arc<Shutdown>) -> Self {
+        let repository = Arc::new(DatabaseRepository::new(db));
+        Self {
+            shutdown,
+            ..Self::with_repositories(repository.clone(), repository)
+        }
+    }
+
+    /// Create the data with the repositories quotes and settings are stored in
+    pub(crate) fn with_repositories(
+        quotes: Arc<dyn QuoteRepository>,
+        settings: Arc<dyn SettingsRepository>,
+    ) -> Self {
         Self {
-            db,
+            quotes,
+            settings,
+            settings_cache: Arc::new(SettingsCache::default()),
+            shutdown: Arc::new(Shutdown::default()),
             menu_selections: DashMap::new(),
         }
     }
 }
 
 pub(crate) fn get_all_commands() -> Vec<poise::Command<Data, Error>> {
-    vec![]
+    let mut commands = vec![]
         .into_iter()
+        .chain(admin::get_all_commands())
         .chain(general::get_all_commands())
+        .chain(privacy::get_all_commands())
         .chain(purge::get_all_commands())
         .chain(quote::get_all_commands())
         .chain(settings::get_all_commands())
-        .collect()
+        .collect::<Vec<_>>();
+
+    // Localize the names and descriptions from the translations
+    localize_commands(&mut commands);
+
+    commands
 }
@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use anyhow::{Context as _, anyhow};
+use poise::{Command, CreateReply, command};
+use serenity::all::{
+    CreateActionRow, CreateAttachment, CreateButton, CreateInteractionResponse, CreateMessage,
+};
+
+use crate::{localize_message, privacy, settings::get_context_settings};
+
+use super::{Context, Data, Error, Result};
+
+pub(super) fn get_all_commands() -> Vec<Command<Data, Error>> {
+    vec![privacy()]
+}
+
+#[command(slash_command, subcommands("export", "delete", "delete_guild"))]
+pub(super) async fn privacy(_ctx: Context<'_>) -> Result {
+    unreachable!();
+}
+
+/// Ask the user to confirm an action with a button, returning whether they did
+async fn confirm(ctx: Context<'_>, prompt: String, button_label: String) -> anyhow::Result<bool> {
+    // Send the prompt
+    let reply = ctx
+        .send(
+            CreateReply::default()
+                .content(prompt)
+                .components(vec![CreateActionRow::Buttons(vec![
+                    CreateButton::new("confirmbutton")
+                        .label(button_label)
+                        .style(serenity::all::ButtonStyle::Danger),
+                ])])
+                .ephemeral(true),
+        )
+        .await
+        .context("Failed to send message")?;
+
+    // Wait for the button to be pressed
+    let interaction = reply
+        .message()
+        .await
+        .context("Failed to get message from reply")?
+        .await_component_interaction(ctx)
+        .author_id(ctx.author().id)
+        .timeout(Duration::from_secs(60))
+        .await;
+    let Some(interaction) = interaction else {
+        return Ok(false);
+    };
+    interaction
+        .create_response(ctx, CreateInteractionResponse::Acknowledge)
+        .await
+        .context("Failed to acknowledge interaction")?;
+
+    Ok(true)
+}
+
+/// Send yourself a file with everything the bot stores about you
+#[command(slash_command)]
+pub(super) async fn export(ctx: Context<'_>) -> Result {
+    // Get the context settings
+    let context_settings = get_context_settings(&ctx)
+        .await
+        .context("Failed to get context settings")?;
+
+    // Defer the response
+    ctx.defer_ephemeral()
+        .await
+        .context("Failed to defer response")?;
+
+    // Collect the data
+    let export = privacy::export_user_data(ctx.data(), ctx.author().id)
+        .await
+        .context("Failed to export user data")?;
+    let attachment = CreateAttachment::bytes(export.to_json()?, "awb-data.json");
+
+    // Send the data in a DM
+    let message = CreateMessage::new()
+        .content(
+            localize_message!("command.privacy.export.message", &context_settings.language)
+                .await
+                .context("Failed to localize message")?,
+        )
+        .add_file(attachment);
+    let response = match ctx.author().direct_message(&ctx, message).await {
+        Ok(_) => localize_message!(
+            "command.privacy.export.response.success",
+            &context_settings.language
+        ),
+        Err(_) => localize_message!(
+            "command.privacy.export.response.error",
+            &context_settings.language
+        ),
+    }
+    .await
+    .context("Failed to localize message")?;
+
+    // Say whether the data was sent
+    ctx.send(CreateReply::default().content(response).ephemeral(true))
+        .await
+        .context("Failed to send message")?;
+
+    // Return ok
+    Ok(())
+}
+
+/// Delete everything the bot stores about you
+#[command(slash_command)]
+pub(super) async fn delete(ctx: Context<'_>) -> Result {
+    // Get the context settings
+    let context_settings = get_context_settings(&ctx)
+        .await
+        .context("Failed to get context settings")?;
+
+    // Make sure the user wants to delete their data
+    let confirmed = confirm(
+        ctx,
+        localize_message!("command.privacy.delete.prompt", &context_settings.language)
+            .await
+            .context("Failed to localize message")?,
+        localize_message!(
+            "command.privacy.delete.button.label",
+            &context_settings.language
+        )
+        .await
+        .context("Failed to localize message")?,
+    )
+    .await?;
+    let response = if confirmed {
+        let count = privacy::delete_user_data(ctx.data(), ctx.author().id)
+            .await
+            .context("Failed to delete user data")?;
+        localize_message!(
+            "command.privacy.delete.response.success",
+            &context_settings.language,
+            count = count
+        )
+        .await
+    } else {
+        localize_message!(
+            "command.privacy.response.cancelled",
+            &context_settings.language
+        )
+        .await
+    }
+    .context("Failed to localize message")?;
+
+    // Say what happened
+    ctx.send(CreateReply::default().content(response).ephemeral(true))
+        .await
+        .context("Failed to send message")?;
+
+    // Return ok
+    Ok(())
+}
+
+/// Delete everything the bot stores about this server
+#[command(
+    slash_command,
+    guild_only,
+    rename = "delete-guild",
+    default_member_permissions = "ADMINISTRATOR",
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(super) async fn delete_guild(ctx: Context<'_>) -> Result {
+    // Get the context settings
+    let context_settings = get_context_settings(&ctx)
+        .await
+        .context("Failed to get context settings")?;
+
+    // Get the guild
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(anyhow!("No guild ID found"))
+        .context("No guild ID found")?;
+
+    // Make sure the admin wants to delete the guild's data
+    let confirmed = confirm(
+        ctx,
+        localize_message!(
+            "command.privacy.delete_guild.prompt",
+            &context_settings.language
+        )
+        .await
+        .context("Failed to localize message")?,
+        localize_message!(
+            "command.privacy.delete.button.label",
+            &context_settings.language
+        )
+        .await
+        .context("Failed to localize message")?,
+    )
+    .await?;
+    let response = if confirmed {
+        let count = privacy::delete_guild_data(ctx.data(), guild_id)
+            .await
+            .context("Failed to delete guild data")?;
+        localize_message!(
+            "command.privacy.delete_guild.response.success",
+            &context_settings.language,
+            count = count
+        )
+        .await
+    } else {
+        localize_message!(
+            "command.privacy.response.cancelled",
+            &context_settings.language
+        )
+        .await
+    }
+    .context("Failed to localize message")?;
+
+    // Say what happened
+    ctx.send(CreateReply::default().content(response).ephemeral(true))
+        .await
+        .context("Failed to send message")?;
+
+    // Return ok
+    Ok(())
+}
@@ -13,38 +13,19 @@ pub(super) fn get_all_commands() -> Vec<poise::Command<Data, Error>> {
     vec![purge()]
 }
 
-#[command(
-    slash_command,
-    name_localized("en-US", "purge"),
-    name_localized("es-419", "purgar"),
-    subcommands("from")
-)]
+#[command(slash_command, subcommands("from"))]
 pub(super) async fn purge(_ctx: Context<'_>) -> Result {
     unreachable!();
 }
 
-#[command(
-    slash_command,
-    name_localized("en-US", "from"),
-    name_localized("es-419", "de"),
-    description_localized("en-US", "Purge messages from a user"),
-    description_localized("es-419", "Limpiar mensajes de un usuario")
-)]
+#[command(slash_command)]
 pub(super) async fn from(
     ctx: Context<'_>,
-    #[name_localized("en-US", "user")]
-    #[name_localized("es-419", "usuario")]
-    #[description_localized("en-US", "The user to purge messages from")]
-    #[description_localized("es-419", "El usuario del que se van a limpiar los mensajes")]
     user: serenity::all::User,
-    #[name_localized("en-US", "limit")]
-    #[name_localized("es-419", "limite")]
-    #[description_localized("en-US", "The number of messages to purge")]
-    #[description_localized("es-419", "El número de mensajes a limpiar")]
     limit: Option<u32>,
 ) -> Result {
     // Get the context settings
-    let context_settings = get_context_settings(&ctx, &ctx.data().db)
+    let context_settings = get_context_settings(&ctx)
         .await
         .context("Failed to get context settings")?;
 
@@ -54,8 +35,8 @@ pub(super) async fn from(
             localize_message!(
                 "command.purge.from.response.pre.limit",
                 &context_settings.language,
-                limit,
-                user.display_name()
+                limit = limit,
+                user = user.display_name()
             )
             .await
             .context("Failed to localize message")?
@@ -63,7 +44,7 @@ pub(super) async fn from(
             localize_message!(
                 "command.purge.from.response.pre.all",
                 &context_settings.language,
-                user.display_name()
+                user = user.display_name()
             )
             .await
             .context("Failed to localize message")?
@@ -105,8 +86,8 @@ pub(super) async fn from(
                 localize_message!(
                     "command.purge.from.response.post",
                     &context_settings.language,
-                    counter,
-                    user.display_name()
+                    count = counter,
+                    user = user.display_name()
                 )
                 .await
                 .context("Failed to localize message")?,
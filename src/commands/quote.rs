@@ -1,10 +1,10 @@
-use std::str::FromStr;
-
 use anyhow::{Context as _, anyhow};
 use poise::{Command, command};
 use serenity::all::{GetMessages, Member, MessageId};
 
-use crate::{db, localize_message, settings::get_context_settings};
+use crate::{
+    db, localization::Argument, localize_message, repository::Quote, settings::get_context_settings,
+};
 
 use super::{Context, Data, Error, Result};
 
@@ -12,34 +12,15 @@ pub(super) fn get_all_commands() -> Vec<Command<Data, Error>> {
     vec![quote()]
 }
 
-#[command(
-    slash_command,
-    guild_only,
-    name_localized("en-US", "quote"),
-    name_localized("es-419", "cita"),
-    subcommands("save", "view")
-)]
+#[command(slash_command, guild_only, subcommands("save", "view"))]
 pub(super) async fn quote(_ctx: Context<'_>) -> Result {
     unreachable!();
 }
 
-#[command(
-    slash_command,
-    name_localized("en-US", "save"),
-    name_localized("es-419", "guardar"),
-    description_localized("en-US", "Save a quote"),
-    description_localized("es-419", "Guardar una cita")
-)]
-pub(super) async fn save(
-    ctx: Context<'_>,
-    #[name_localized("en-US", "message")]
-    #[name_localized("es-419", "mensaje")]
-    #[description_localized("en-US", "The message ID of the quote to save")]
-    #[description_localized("es-419", "El ID del mensaje de la cita a guardar")]
-    message: Option<MessageId>,
-) -> Result {
+#[command(slash_command)]
+pub(super) async fn save(ctx: Context<'_>, message: Option<MessageId>) -> Result {
     // Get the context settings
-    let context_settings = get_context_settings(&ctx, &ctx.data().db)
+    let context_settings = get_context_settings(&ctx)
         .await
         .context("Failed to get context settings")?;
 
@@ -62,26 +43,26 @@ pub(super) async fn save(
             .to_owned()
     };
 
-    // Create the database entry
-    db::quotebook::create_entry(
-        &ctx.data().db,
-        message.id.to_string(),
-        ctx.guild_id()
-            .ok_or(anyhow!("No guild ID found"))
-            .context("No guild ID found")?
-            .to_string(),
-        message.author.id.to_string(),
-        message.id.created_at().naive_utc(),
-    )
-    .await
-    .context("Failed to create entry in quotebook table")?;
+    // Save the quote
+    ctx.data()
+        .quotes
+        .add_quote(
+            message.id,
+            ctx.guild_id()
+                .ok_or(anyhow!("No guild ID found"))
+                .context("No guild ID found")?,
+            message.author.id,
+            message.id.created_at().naive_utc(),
+        )
+        .await
+        .context("Failed to save quote")?;
 
     // Say that the quote was saved
     ctx.say(
         localize_message!(
             "command.quote.save.response",
             &context_settings.language,
-            message.link()
+            link = message.link()
         )
         .await
         .context("Failed to localize message")?,
@@ -95,15 +76,11 @@ pub(super) async fn save(
 
 /// Create a preview for a quote
 #[allow(clippy::needless_question_mark)]
-async fn create_quote_preview(
-    ctx: Context<'_>,
-    entry: db::quotebook::Model,
-) -> anyhow::Result<String> {
-    // Get the language
-    let language = get_context_settings(&ctx, &ctx.data().db)
+async fn create_quote_preview(ctx: Context<'_>, entry: Quote) -> anyhow::Result<String> {
+    // Get the context settings
+    let context_settings = get_context_settings(&ctx)
         .await
-        .context("Failed to get context settings")?
-        .language;
+        .context("Failed to get context settings")?;
 
     // Get the quote id
     let quote_id = entry.id;
@@ -111,10 +88,7 @@ async fn create_quote_preview(
     // Get the quote message
     let message = ctx
         .channel_id()
-        .message(
-            ctx,
-            MessageId::from_str(&entry.message_id).context("Invalid message ID")?,
-        )
+        .message(ctx, entry.message_id.0)
         .await
         .context("Could not get message")?;
 
@@ -151,51 +125,28 @@ async fn create_quote_preview(
     // Create the preview
     Ok(localize_message!(
         "command.quote.view.preview",
-        &language,
-        quote_id,
-        author_name,
-        message_content,
-        message_link
+        &context_settings.language,
+        id = quote_id,
+        author = author_name,
+        content = message_content,
+        date = context_settings.date(entry.datetime.and_utc()),
+        ago = Argument::Relative(entry.datetime.and_utc()),
+        link = message_link
     )
     .await
     .context("Failed to localize message")?)
 }
 
-#[command(
-    slash_command,
-    name_localized("en-US", "view"),
-    name_localized("es-419", "ver"),
-    description_localized("en-US", "View some quotes"),
-    description_localized("es-419", "Ver algunas citas")
-)]
+#[command(slash_command)]
 pub(super) async fn view(
     ctx: Context<'_>,
-
-    #[name_localized("en-US", "limit")]
-    #[name_localized("es-419", "limite")]
-    #[description_localized("en-US", "The number of quotes to view")]
-    #[description_localized("es-419", "El número de citas a ver")]
     limit: Option<u8>,
-
-    #[name_localized("en-US", "author")]
-    #[name_localized("es-419", "autor")]
-    #[description_localized("en-US", "The author of the quotes to view")]
-    #[description_localized("es-419", "El autor de las citas a ver")]
     author: Option<Member>,
-    // #[name_localized("en-US", "start_date")]
-    // #[name_localized("es-419", "fecha_inicio")]
-    // #[description_localized("en-US", "The start date of the quotes to view")]
-    // #[description_localized("es-419", "La fecha de inicio de las citas a ver")]
     // start_date: Option<NaiveDateTime>,
-
-    // #[name_localized("en-US", "end_date")]
-    // #[name_localized("es-419", "fecha_fin")]
-    // #[description_localized("en-US", "The end date of the quotes to view")]
-    // #[description_localized("es-419", "La fecha de fin de las citas a ver")]
     // end_date: Option<NaiveDateTime>,
 ) -> Result {
     // Get the context settings
-    let context_settings = get_context_settings(&ctx, &ctx.data().db)
+    let context_settings = get_context_settings(&ctx)
         .await
         .context("Failed to get context settings")?;
 
@@ -206,16 +157,12 @@ pub(super) async fn view(
     let mut filters = db::quotebook::EntryFilters::new();
 
     // Add filters as needed
-    filters = filters.guild_id(
-        ctx.guild_id()
-            .ok_or(anyhow!("No guild ID found"))?
-            .to_string(),
-    );
+    filters = filters.guild_id(ctx.guild_id().ok_or(anyhow!("No guild ID found"))?);
     if let Some(limit) = limit {
         filters = filters.limit(limit);
     }
     if let Some(author) = author {
-        filters = filters.author_id(author.user.id.to_string());
+        filters = filters.author_id(author.user.id);
     }
     // if let Some(start_date) = start_date {
     //     filters = filters.datetime_start(start_date);
@@ -224,10 +171,13 @@ pub(super) async fn view(
     //     filters = filters.datetime_end(end_date);
     // }
 
-    // Get the entries from the database
-    let entries = db::quotebook::get_entries(&ctx.data().db, filters)
+    // Get the quotes
+    let entries = ctx
+        .data()
+        .quotes
+        .get_quotes(&filters)
         .await
-        .context("Could not get entries from database")?;
+        .context("Could not get quotes")?;
 
     // Branch bases on entries being empty or not
     if entries.is_empty() {
@@ -254,7 +204,7 @@ pub(super) async fn view(
             localize_message!(
                 "command.quote.view.response.previews",
                 &context_settings.language,
-                previews.join("\n")
+                previews = previews.join("\n")
             )
             .await
             .context("Failed to localize message")?,
@@ -1,6 +1,6 @@
 use std::{time::Duration, vec};
 
-use anyhow::Context as _;
+use anyhow::{Context as _, anyhow};
 use poise::{Command, CreateReply, command};
 use serenity::all::{
     ComponentInteractionDataKind, CreateActionRow, CreateButton, CreateInteractionResponse,
@@ -8,7 +8,13 @@ use serenity::all::{
     CreateSelectMenuOption,
 };
 
-use crate::{db, localization::Language, localize_message, settings::get_context_settings};
+use crate::{
+    localize_message,
+    settings::{
+        SettingKind, SettingTarget, TIMEZONE, get_channel_settings, get_context_settings,
+        get_guild_settings, get_setting, get_user_settings, scope_settings, update_setting,
+    },
+};
 
 use super::{Context, Data, Error, Result};
 
@@ -16,48 +22,228 @@ pub(super) fn get_all_commands() -> Vec<Command<Data, Error>> {
     vec![settings()]
 }
 
+#[command(slash_command, subcommands("user", "channel", "guild", "timezone"))]
+pub(super) async fn settings(_ctx: Context<'_>) -> Result {
+    unreachable!();
+}
+
+#[command(slash_command)]
+pub(super) async fn user(ctx: Context<'_>) -> Result {
+    settings_menu(ctx, SettingTarget::User(ctx.author().id)).await
+}
+
 #[command(
     slash_command,
-    name_localized("en-US", "settings"),
-    name_localized("es-419", "ajustes"),
-    subcommands("user")
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS",
+    default_member_permissions = "MANAGE_CHANNELS"
 )]
-pub(super) async fn settings(_ctx: Context<'_>) -> Result {
-    unreachable!();
+pub(super) async fn channel(ctx: Context<'_>) -> Result {
+    settings_menu(
+        ctx,
+        SettingTarget::Channel(
+            ctx.guild_id()
+                .ok_or(anyhow!("No guild ID found"))
+                .context("No guild ID found")?,
+            ctx.channel_id(),
+        ),
+    )
+    .await
 }
 
 #[command(
     slash_command,
-    name_localized("en-US", "user"),
-    name_localized("es-419", "usuario"),
-    description_localized("en-US", "Open the settings menu for yourself"),
-    description_localized("es-419", "Abre el menú de ajustes para ti mismo")
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    default_member_permissions = "MANAGE_GUILD"
 )]
-pub(super) async fn user(ctx: Context<'_>) -> Result {
+pub(super) async fn guild(ctx: Context<'_>) -> Result {
+    settings_menu(
+        ctx,
+        SettingTarget::Guild(
+            ctx.guild_id()
+                .ok_or(anyhow!("No guild ID found"))
+                .context("No guild ID found")?,
+        ),
+    )
+    .await
+}
+
+/// Autocomplete a timezone name
+async fn autocomplete_timezone<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    let partial = partial.to_lowercase();
+    chrono_tz::TZ_VARIANTS
+        .iter()
+        .map(|timezone| timezone.name())
+        .filter(move |name| name.to_lowercase().contains(&partial))
+        .take(25)
+        .map(str::to_owned)
+}
+
+#[command(slash_command)]
+pub(super) async fn timezone(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete_timezone"] timezone: String,
+    server: Option<bool>,
+) -> Result {
     // Get the context settings
-    let context_settings = get_context_settings(&ctx, &ctx.data().db)
+    let context_settings = get_context_settings(&ctx)
+        .await
+        .context("Failed to get context settings")?;
+
+    // Get the target of the setting
+    let target = if server.unwrap_or(false) {
+        // Make sure the command is used in a server
+        let Some(guild_id) = ctx.guild_id() else {
+            ctx.send(
+                CreateReply::default()
+                    .content(
+                        localize_message!(
+                            "command.settings.timezone.response.error.noguild",
+                            &context_settings.language
+                        )
+                        .await
+                        .context("Failed to localize message")?,
+                    )
+                    .ephemeral(true),
+            )
+            .await
+            .context("Failed to send message")?;
+            return Ok(());
+        };
+
+        // Make sure the user can manage the server
+        let can_manage_guild = ctx
+            .author_member()
+            .await
+            .and_then(|member| member.permissions)
+            .is_some_and(|permissions| permissions.manage_guild());
+        if !can_manage_guild {
+            ctx.send(
+                CreateReply::default()
+                    .content(
+                        localize_message!(
+                            "command.settings.timezone.response.error.permission",
+                            &context_settings.language
+                        )
+                        .await
+                        .context("Failed to localize message")?,
+                    )
+                    .ephemeral(true),
+            )
+            .await
+            .context("Failed to send message")?;
+            return Ok(());
+        }
+        SettingTarget::Guild(guild_id)
+    } else {
+        SettingTarget::User(ctx.author().id)
+    };
+
+    // Make sure the timezone is valid
+    if TIMEZONE.validate(&timezone).is_err() {
+        ctx.send(
+            CreateReply::default()
+                .content(
+                    localize_message!(
+                        "command.settings.timezone.response.error.invalid",
+                        &context_settings.language,
+                        timezone = &timezone
+                    )
+                    .await
+                    .context("Failed to localize message")?,
+                )
+                .ephemeral(true),
+        )
+        .await
+        .context("Failed to send message")?;
+        return Ok(());
+    }
+
+    // Update the setting
+    update_setting(
+        ctx.data().settings.as_ref(),
+        &ctx.data().settings_cache,
+        target,
+        &TIMEZONE,
+        Some(timezone.clone()),
+    )
+    .await
+    .context("Failed to update setting")?;
+
+    // Say that the timezone was changed
+    let content = match target {
+        SettingTarget::Guild(_) => {
+            localize_message!(
+                "command.settings.timezone.response.serversuccess",
+                &context_settings.language,
+                timezone = &timezone
+            )
+            .await
+        }
+        _ => {
+            localize_message!(
+                "command.settings.timezone.response.success",
+                &context_settings.language,
+                timezone = &timezone
+            )
+            .await
+        }
+    }
+    .context("Failed to localize message")?;
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await
+        .context("Failed to send message")?;
+
+    // Return ok
+    Ok(())
+}
+
+/// The value of the menu option that removes a setting, so it is inherited again
+const UNSET_VALUE: &str = "$unset";
+
+/// Get the translation key of a settings menu message for a target
+fn message_key(target: SettingTarget, key: &str) -> String {
+    match target {
+        SettingTarget::User(_) => format!("command.settings.user.{key}"),
+        SettingTarget::Channel(_, _) => format!("command.settings.channel.{key}"),
+        SettingTarget::Guild(_) => format!("command.settings.guild.{key}"),
+    }
+}
+
+/// Open a settings menu for a target and handle the interactions on it
+async fn settings_menu(ctx: Context<'_>, target: SettingTarget) -> Result {
+    // Get the context settings
+    let context_settings = get_context_settings(&ctx)
         .await
         .context("Failed to get context settings")?;
 
     // Create the reply
     let reply = {
+        // Get the settings that can be set for the target, along with their labels
+        let mut options = Vec::new();
+        for setting in scope_settings(target.scope()) {
+            options.push(CreateSelectMenuOption::new(
+                localize_message!(setting.label, &context_settings.language)
+                    .await
+                    .context("Failed to localize message")?,
+                setting.key,
+            ));
+        }
+
         // Create the components
         let components = vec![
             CreateActionRow::SelectMenu(CreateSelectMenu::new(
                 "menu",
-                CreateSelectMenuKind::String {
-                    options: vec![CreateSelectMenuOption::new(
-                        localize_message!("settings.language.label", &context_settings.language,)
-                            .await
-                            .context("Failed to localize message")?,
-                        "language",
-                    )],
-                },
+                CreateSelectMenuKind::String { options },
             )),
             CreateActionRow::Buttons(vec![
                 CreateButton::new("viewbutton").label(
                     localize_message!(
-                        "command.settings.user.button.view.label",
+                        message_key(target, "button.view.label"),
                         &context_settings.language
                     )
                     .await
@@ -65,7 +251,7 @@ pub(super) async fn user(ctx: Context<'_>) -> Result {
                 ),
                 CreateButton::new("editbutton").label(
                     localize_message!(
-                        "command.settings.user.button.edit.label",
+                        message_key(target, "button.edit.label"),
                         &context_settings.language
                     )
                     .await
@@ -79,7 +265,7 @@ pub(super) async fn user(ctx: Context<'_>) -> Result {
             CreateReply::default()
                 .content(
                     localize_message!(
-                        "command.settings.user.response.initial",
+                        message_key(target, "response.initial"),
                         &context_settings.language
                     )
                     .await
@@ -128,40 +314,52 @@ pub(super) async fn user(ctx: Context<'_>) -> Result {
                     .await
                     .context("Failed to defer interaction")?;
 
-                // Get the selection
-                let selection = ctx
+                // Get the selected setting
+                let setting = ctx
                     .data()
                     .menu_selections
-                    .get(&(interaction.message.id, interaction.user.id));
+                    .get(&(interaction.message.id, interaction.user.id))
+                    .and_then(|selection| get_setting(selection.as_str()));
 
-                if let Some(selection) = selection {
+                if let Some(setting) = setting {
                     // Get the name of the setting
-                    let setting_name = match selection.as_str() {
-                        "language" => {
-                            localize_message!("settings.language.label", &context_settings.language)
-                                .await
-                                .context("Failed to localize message")?
-                        }
-                        _ => unreachable!("Invalid selection"),
-                    };
+                    let setting_name = localize_message!(setting.label, &context_settings.language)
+                        .await
+                        .context("Failed to localize message")?;
 
-                    // Get the value of the setting
-                    let setting_value = match selection.as_str() {
-                        "language" => match context_settings.language {
-                            Language::English => localize_message!(
-                                "settings.language.names.en",
-                                &context_settings.language
-                            )
+                    // Get the value of the setting for the target
+                    let value = match target {
+                        SettingTarget::User(_) => get_user_settings(&ctx)
                             .await
-                            .context("Failed to localize message")?,
-                            Language::Spanish => localize_message!(
-                                "settings.language.names.es",
-                                &context_settings.language
-                            )
+                            .context("Failed to get user settings")?
+                            .values
+                            .get(setting)
+                            .unwrap_or(setting.default)
+                            .to_owned(),
+                        SettingTarget::Channel(_, _) => get_channel_settings(&ctx)
                             .await
-                            .context("Failed to localize message")?,
-                        },
-                        _ => unreachable!("Invalid selection"),
+                            .context("Failed to get channel settings")?
+                            .and_then(|channel_settings| {
+                                channel_settings.values.get(setting).map(str::to_owned)
+                            })
+                            .unwrap_or(setting.default.to_owned()),
+                        SettingTarget::Guild(_) => get_guild_settings(&ctx)
+                            .await
+                            .context("Failed to get guild settings")?
+                            .and_then(|guild_settings| {
+                                guild_settings.values.get(setting).map(str::to_owned)
+                            })
+                            .unwrap_or(setting.default.to_owned()),
+                    };
+
+                    // Get the label of the value
+                    let setting_value = if let Some(label) = setting.value_label(&value) {
+                        label
+                            .localize(&context_settings.language)
+                            .await
+                            .context("Failed to localize message")?
+                    } else {
+                        value
                     };
 
                     // Create the followup response
@@ -171,10 +369,10 @@ pub(super) async fn user(ctx: Context<'_>) -> Result {
                             CreateInteractionResponseFollowup::default()
                                 .content(
                                     localize_message!(
-                                        "command.settings.user.response.view",
+                                        message_key(target, "response.view"),
                                         &context_settings.language,
-                                        setting_name,
-                                        setting_value
+                                        setting = setting_name,
+                                        value = setting_value
                                     )
                                     .await
                                     .context("Failed to localize message")?,
@@ -191,7 +389,7 @@ pub(super) async fn user(ctx: Context<'_>) -> Result {
                             CreateInteractionResponseFollowup::default()
                                 .content(
                                     localize_message!(
-                                        "command.settings.user.response.error.noselection",
+                                        message_key(target, "response.error.noselection"),
                                         &context_settings.language
                                     )
                                     .await
@@ -210,49 +408,36 @@ pub(super) async fn user(ctx: Context<'_>) -> Result {
                     .await
                     .context("Failed to defer interaction")?;
 
-                // Get the selection
-                let selection = ctx
+                // Get the selected setting
+                let setting = ctx
                     .data()
                     .menu_selections
-                    .get(&(interaction.message.id, interaction.user.id));
+                    .get(&(interaction.message.id, interaction.user.id))
+                    .and_then(|selection| get_setting(selection.as_str()));
 
-                if let Some(selection) = selection {
+                if let Some(setting) = setting {
                     // Get the name of the setting
-                    let setting_name = match selection.as_str() {
-                        "language" => {
-                            localize_message!("settings.language.label", &context_settings.language)
-                                .await
-                                .context("Failed to localize message")?
-                        }
-                        _ => unreachable!("Invalid selection"),
-                    };
+                    let setting_name = localize_message!(setting.label, &context_settings.language)
+                        .await
+                        .context("Failed to localize message")?;
 
                     // Get the valid values for the setting, along with their labels
-                    let valid_values = match selection.as_str() {
-                        "language" => {
-                            vec![
-                                (
-                                    "en",
-                                    localize_message!(
-                                        "settings.language.names.en",
-                                        &context_settings.language
-                                    )
-                                    .await
-                                    .context("Failed to localize message")?,
-                                ),
-                                (
-                                    "es",
-                                    localize_message!(
-                                        "settings.language.names.es",
-                                        &context_settings.language
-                                    )
-                                    .await
-                                    .context("Failed to localize message")?,
-                                ),
-                            ]
-                        }
-                        _ => unreachable!("Invalid selection"),
-                    };
+                    let mut valid_values = vec![(
+                        UNSET_VALUE.into(),
+                        localize_message!("settings.unset", &context_settings.language)
+                            .await
+                            .context("Failed to localize message")?,
+                    )];
+                    for choice in setting.choices() {
+                        valid_values.push((
+                            choice.value,
+                            choice
+                                .label
+                                .localize(&context_settings.language)
+                                .await
+                                .context("Failed to localize message")?,
+                        ));
+                    }
 
                     // Create the followup response components
                     let components = vec![CreateActionRow::SelectMenu(CreateSelectMenu::new(
@@ -263,27 +448,37 @@ pub(super) async fn user(ctx: Context<'_>) -> Result {
                                 .map(|value| {
                                     CreateSelectMenuOption::new(
                                         value.1.to_owned(),
-                                        value.0.to_owned(),
+                                        value.0.to_string(),
                                     )
                                 })
                                 .collect(),
                         },
                     ))];
 
+                    // Point the user to the command for settings that can't be picked from a menu
+                    let content = if let SettingKind::Timezone = setting.kind {
+                        localize_message!(
+                            "command.settings.timezone.response.hint",
+                            &context_settings.language,
+                            setting = &setting_name
+                        )
+                        .await
+                    } else {
+                        localize_message!(
+                            message_key(target, "response.edit.initial"),
+                            &context_settings.language,
+                            setting = &setting_name
+                        )
+                        .await
+                    }
+                    .context("Failed to localize message")?;
+
                     // Create the followup response
                     let reply = interaction
                         .create_followup(
                             ctx,
                             CreateInteractionResponseFollowup::default()
-                                .content(
-                                    localize_message!(
-                                        "command.settings.user.response.edit.initial",
-                                        &context_settings.language,
-                                        &setting_name
-                                    )
-                                    .await
-                                    .context("Failed to localize message")?,
-                                )
+                                .content(content)
                                 .components(components)
                                 .ephemeral(true),
                         )
@@ -309,36 +504,21 @@ pub(super) async fn user(ctx: Context<'_>) -> Result {
                                 if let ComponentInteractionDataKind::StringSelect { values } =
                                     &inner_interaction.data.kind
                                 {
-                                    // Get the user's selection
+                                    // Get the user's selection, where unsetting removes the value
                                     let inner_selection = &values[0];
+                                    let value = (inner_selection != UNSET_VALUE)
+                                        .then(|| inner_selection.to_owned());
 
                                     // Update the setting
-                                    match selection.as_str() {
-                                        "language" => match inner_selection.as_str() {
-                                            "en" => {
-                                                db::user_settings::update_entry(
-                                                    &ctx.data().db,
-                                                    inner_interaction.user.id,
-                                                    db::user_settings::Column::Language,
-                                                    db::user_settings::Language::English,
-                                                )
-                                                .await
-                                                .context("Failed to update user settings")?;
-                                            }
-                                            "es" => {
-                                                db::user_settings::update_entry(
-                                                    &ctx.data().db,
-                                                    inner_interaction.user.id,
-                                                    db::user_settings::Column::Language,
-                                                    db::user_settings::Language::Spanish,
-                                                )
-                                                .await
-                                                .context("Failed to update user settings")?;
-                                            }
-                                            _ => unreachable!("Invalid selection"),
-                                        },
-                                        _ => unreachable!("Invalid selection"),
-                                    }
+                                    update_setting(
+                                        ctx.data().settings.as_ref(),
+                                        &ctx.data().settings_cache,
+                                        target,
+                                        setting,
+                                        value,
+                                    )
+                                    .await
+                                    .context("Failed to update setting")?;
 
                                     // Acknowledge the interaction
                                     inner_interaction
@@ -347,9 +527,12 @@ pub(super) async fn user(ctx: Context<'_>) -> Result {
                                             CreateInteractionResponseFollowup::default()
                                                 .content(
                                                     localize_message!(
-                                                        "command.settings.user.response.edit.success",
+                                                        message_key(
+                                                            target,
+                                                            "response.edit.success"
+                                                        ),
                                                         &context_settings.language,
-                                                        &setting_name
+                                                        setting = &setting_name
                                                     )
                                                     .await
                                                     .context("Failed to localize message")?,
@@ -372,7 +555,7 @@ pub(super) async fn user(ctx: Context<'_>) -> Result {
                             ctx,
                             CreateInteractionResponseFollowup::default().content(
                                 localize_message!(
-                                    "command.settings.user.response.error.noselection",
+                                    message_key(target, "response.error.noselection"),
                                     &context_settings.language
                                 )
                                 .await
@@ -0,0 +1,332 @@
+use std::{
+    env,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::OnceLock,
+};
+
+use anyhow::{Context as _, anyhow};
+use sea_orm::sqlx::sqlite::SqliteJournalMode;
+use serde::Deserialize;
+use serenity::all::{GuildId, UserId};
+use strum::EnumString;
+use tracing::Level;
+
+/// The file the configuration is read from when `CONFIG_PATH` is not set
+const DEFAULT_CONFIG_PATH: &str = "config.yaml";
+
+/// The loaded configuration
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// How much the bot logs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub(crate) enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl From<LogLevel> for Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Trace => Level::TRACE,
+            LogLevel::Debug => Level::DEBUG,
+            LogLevel::Info => Level::INFO,
+            LogLevel::Warn => Level::WARN,
+            LogLevel::Error => Level::ERROR,
+        }
+    }
+}
+
+/// The configuration of development mode
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct DevelopmentConfig {
+    /// Whether commands are registered in the development guilds instead of globally
+    pub enabled: bool,
+    /// The guilds commands are registered in during development
+    pub guilds: Vec<GuildId>,
+}
+
+/// The configuration of the database connection
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct DatabaseConfig {
+    /// The url of the database, or a SQLite file in the data directory if not set
+    pub url: Option<String>,
+    /// The most connections to open, or the driver's default if not set
+    pub pool_size: Option<u32>,
+    /// The journal mode of SQLite database files
+    pub sqlite_journal_mode: String,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            pool_size: None,
+            sqlite_journal_mode: "WAL".to_owned(),
+        }
+    }
+}
+
+/// The configuration of the periodic SQLite snapshots
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct BackupConfig {
+    /// The directory snapshots are written to, or `backups` in the data directory if not set
+    pub dir: Option<PathBuf>,
+    /// How many hours to wait between snapshots, where 0 turns them off
+    pub interval_hours: u64,
+    /// How many snapshots to keep
+    pub keep: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            interval_hours: 24,
+            keep: 7,
+        }
+    }
+}
+
+/// The configuration of the bot
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    /// The file the Discord token is read from
+    pub token_path: PathBuf,
+    /// The directory translations are loaded from
+    pub translations_dir: PathBuf,
+    /// The directory the bot keeps its data in
+    pub data_dir: PathBuf,
+    pub log_level: LogLevel,
+    /// How many seconds to wait for commands and background jobs to finish when stopping
+    pub shutdown_timeout_secs: u64,
+    /// The users that can use owner commands, besides the owners of the application
+    pub owners: Vec<UserId>,
+    pub development: DevelopmentConfig,
+    pub database: DatabaseConfig,
+    pub backup: BackupConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            token_path: PathBuf::from("token.txt"),
+            translations_dir: PathBuf::from("./lang"),
+            data_dir: PathBuf::from("./data"),
+            log_level: LogLevel::Info,
+            shutdown_timeout_secs: 8,
+            owners: Vec::new(),
+            development: DevelopmentConfig::default(),
+            database: DatabaseConfig::default(),
+            backup: BackupConfig::default(),
+        }
+    }
+}
+
+/// Parse a value from an environment variable
+fn parse_env<T>(name: &str, value: &str) -> Result<T, anyhow::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|err| anyhow!("Invalid value `{}` for {}: {}", value, name, err))
+}
+
+/// Parse a comma separated list from an environment variable
+fn parse_env_list<T>(name: &str, value: &str) -> Result<Vec<T>, anyhow::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .split(',')
+        .filter(|item| !item.trim().is_empty())
+        .map(|item| parse_env(name, item))
+        .collect()
+}
+
+impl Config {
+    /// Read the configuration from a YAML file
+    fn from_file(path: &Path) -> Result<Self, anyhow::Error> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Could not read config file {}", path.display()))?;
+        serde_yaml::from_str(&contents)
+            .with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Change the configuration with the environment variables that are set
+    fn apply_env(&mut self, get: impl Fn(&str) -> Option<String>) -> Result<(), anyhow::Error> {
+        if let Some(value) = get("TOKEN_PATH") {
+            self.token_path = value.into();
+        }
+        if let Some(value) = get("TRANSLATIONS_DIR") {
+            self.translations_dir = value.into();
+        }
+        if let Some(value) = get("DATA_DIR") {
+            self.data_dir = value.into();
+        }
+        if let Some(value) = get("LOG_LEVEL") {
+            self.log_level = parse_env("LOG_LEVEL", &value)?;
+        }
+        if let Some(value) = get("SHUTDOWN_TIMEOUT_SECS") {
+            self.shutdown_timeout_secs = parse_env("SHUTDOWN_TIMEOUT_SECS", &value)?;
+        }
+        if let Some(value) = get("OWNERS") {
+            self.owners = parse_env_list("OWNERS", &value)?;
+        }
+        if let Some(value) = get("DEVELOPMENT") {
+            self.development.enabled = parse_env("DEVELOPMENT", &value)?;
+        }
+        if let Some(value) = get("DEV_GUILDS") {
+            self.development.guilds = parse_env_list("DEV_GUILDS", &value)?;
+        }
+        if let Some(value) = get("DATABASE_URL") {
+            self.database.url = Some(value);
+        }
+        if let Some(value) = get("DATABASE_POOL_SIZE") {
+            self.database.pool_size = Some(parse_env("DATABASE_POOL_SIZE", &value)?);
+        }
+        if let Some(value) = get("SQLITE_JOURNAL_MODE") {
+            self.database.sqlite_journal_mode = value;
+        }
+        if let Some(value) = get("BACKUP_DIR") {
+            self.backup.dir = Some(value.into());
+        }
+        if let Some(value) = get("BACKUP_INTERVAL_HOURS") {
+            self.backup.interval_hours = parse_env("BACKUP_INTERVAL_HOURS", &value)?;
+        }
+        if let Some(value) = get("BACKUP_KEEP") {
+            self.backup.keep = parse_env("BACKUP_KEEP", &value)?;
+        }
+        Ok(())
+    }
+
+    /// Make sure the configuration can be used
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.development.enabled && self.development.guilds.is_empty() {
+            return Err(anyhow!(
+                "Development mode needs at least one guild in development.guilds or DEV_GUILDS"
+            ));
+        }
+        if self.database.pool_size == Some(0) {
+            return Err(anyhow!("database.pool_size must be at least 1"));
+        }
+        if SqliteJournalMode::from_str(&self.database.sqlite_journal_mode).is_err() {
+            return Err(anyhow!(
+                "Invalid SQLite journal mode `{}`",
+                self.database.sqlite_journal_mode
+            ));
+        }
+        if self.backup.keep == 0 {
+            return Err(anyhow!("backup.keep must be at least 1"));
+        }
+        Ok(())
+    }
+
+    /// Load the configuration from a file, if it exists, and the environment
+    fn load(
+        path: Option<&Path>,
+        get: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, anyhow::Error> {
+        // Read the file, which is only required when its path was given
+        let default_path = Path::new(DEFAULT_CONFIG_PATH);
+        let mut config = match path {
+            Some(path) => Self::from_file(path)?,
+            None if default_path.exists() => Self::from_file(default_path)?,
+            None => Self::default(),
+        };
+
+        // Override it with the environment
+        config.apply_env(get)?;
+
+        // Return the configuration if it is valid
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Load the configuration from a file, `CONFIG_PATH` or `config.yaml`, and the environment
+pub(crate) fn init(path: Option<PathBuf>) -> Result<&'static Config, anyhow::Error> {
+    let path = path.or_else(|| env::var_os("CONFIG_PATH").map(PathBuf::from));
+    let config = Config::load(path.as_deref(), |name| env::var(name).ok())?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// Get the configuration, which is the default if it was never loaded
+pub(crate) fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_config_file_and_env() {
+        let config = serde_yaml::from_str::<Config>(
+            "log_level: debug\nowners: [1235772100384526377]\ndevelopment:\n  enabled: true\n  guilds: [\"2\"]\ndatabase:\n  pool_size: 5\n",
+        )
+        .unwrap();
+        assert_eq!(config.log_level, LogLevel::Debug);
+        assert_eq!(config.owners, vec![UserId::new(1235772100384526377)]);
+        assert_eq!(config.development.guilds, vec![GuildId::new(2)]);
+        assert_eq!(config.database.pool_size, Some(5));
+        assert_eq!(config.backup, BackupConfig::default());
+        assert!(config.validate().is_ok());
+
+        // The example file documents the defaults
+        assert_eq!(
+            serde_yaml::from_str::<Config>(include_str!("../config.example.yaml")).unwrap(),
+            Config::default()
+        );
+
+        // Unknown keys are mistakes
+        assert!(serde_yaml::from_str::<Config>("log_levle: debug").is_err());
+
+        // The environment overrides the file
+        let env = HashMap::from([
+            ("LOG_LEVEL", "WARN"),
+            ("OWNERS", "1, 2"),
+            ("DEVELOPMENT", "false"),
+            ("BACKUP_KEEP", "3"),
+        ]);
+        let mut overridden = config.clone();
+        overridden
+            .apply_env(|name| env.get(name).map(|value| value.to_string()))
+            .unwrap();
+        assert_eq!(overridden.log_level, LogLevel::Warn);
+        assert_eq!(overridden.owners, vec![UserId::new(1), UserId::new(2)]);
+        assert!(!overridden.development.enabled);
+        assert_eq!(overridden.backup.keep, 3);
+
+        // Invalid values are reported
+        let mut invalid = config.clone();
+        assert!(
+            invalid
+                .apply_env(|name| (name == "DATABASE_POOL_SIZE").then(|| "many".to_owned()))
+                .is_err()
+        );
+        let mut invalid = Config::default();
+        invalid.development.enabled = true;
+        assert!(invalid.validate().is_err());
+        let mut invalid = Config::default();
+        invalid.database.sqlite_journal_mode = "WAL; DROP TABLE quotebook".to_owned();
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use chrono::Utc;
+use sea_orm::{ConnectionTrait as _, DatabaseBackend, DbConn};
+use tokio::task::JoinHandle;
+use tracing::{Level, event};
+
+use super::connection::data_dir;
+use crate::{config::config, shutdown::Shutdown};
+
+/// The prefix of the names of snapshot files
+const SNAPSHOT_PREFIX: &str = "awb-";
+
+/// The extension of snapshot files
+const SNAPSHOT_EXTENSION: &str = "sqlite";
+
+/// Get the directory snapshots are written to
+fn backup_dir() -> PathBuf {
+    config()
+        .backup
+        .dir
+        .clone()
+        .unwrap_or_else(|| data_dir().join("backups"))
+}
+
+/// Write a compacted copy of a SQLite database to a directory with `VACUUM INTO`
+pub(crate) async fn snapshot(db: &DbConn, dir: &Path) -> Result<PathBuf, anyhow::Error> {
+    if db.get_database_backend() != DatabaseBackend::Sqlite {
+        anyhow::bail!("Snapshots are only supported for SQLite databases");
+    }
+
+    // Get the path of the snapshot
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Could not create backup directory {}", dir.display()))?;
+    let path = dir.join(format!(
+        "{}{}.{}",
+        SNAPSHOT_PREFIX,
+        Utc::now().format("%Y%m%dT%H%M%SZ"),
+        SNAPSHOT_EXTENSION
+    ));
+
+    // Write the snapshot
+    let quoted_path = path.to_string_lossy().replace('\'', "''");
+    db.execute_unprepared(&format!("VACUUM INTO '{}'", quoted_path))
+        .await
+        .context("Failed to write database snapshot")?;
+
+    // Return the path
+    Ok(path)
+}
+
+/// Remove all but the newest snapshots in a directory
+pub(crate) fn prune_snapshots(dir: &Path, keep: usize) -> Result<(), anyhow::Error> {
+    // Get the snapshots, which sort by the time they were taken
+    let mut snapshots = fs::read_dir(dir)
+        .with_context(|| format!("Could not read backup directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == SNAPSHOT_EXTENSION)
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(SNAPSHOT_PREFIX))
+        })
+        .collect::<Vec<_>>();
+    snapshots.sort();
+
+    // Remove the oldest ones
+    let excess = snapshots.len().saturating_sub(keep);
+    for path in &snapshots[..excess] {
+        fs::remove_file(path)
+            .with_context(|| format!("Could not remove snapshot {}", path.display()))?;
+    }
+
+    // Return ok
+    Ok(())
+}
+
+/// Periodically snapshot a SQLite database until the bot stops, if snapshots are enabled
+pub(crate) fn spawn_snapshots(db: DbConn, shutdown: Arc<Shutdown>) -> Option<JoinHandle<()>> {
+    // Snapshots only make sense for SQLite
+    if db.get_database_backend() != DatabaseBackend::Sqlite {
+        return None;
+    }
+
+    // Get the snapshot settings
+    let interval_hours = config().backup.interval_hours;
+    if interval_hours == 0 {
+        event!(Level::INFO, "Database snapshots are disabled");
+        return None;
+    }
+    let keep = config().backup.keep;
+    let dir = backup_dir();
+
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_hours * 60 * 60));
+        // The first tick completes immediately, so skip it to not snapshot on every start
+        interval.tick().await;
+        loop {
+            // A snapshot that has started is finished before stopping
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.stopped() => break,
+            }
+            match snapshot(&db, &dir).await {
+                Ok(path) => event!(Level::INFO, "Wrote database snapshot {}", path.display()),
+                Err(err) => {
+                    event!(Level::ERROR, "{:#}", err);
+                    continue;
+                }
+            }
+            if let Err(err) = prune_snapshots(&dir, keep) {
+                event!(Level::ERROR, "{:#}", err);
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use sea_orm::Database;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snapshot_and_prune() {
+        let dir = env::temp_dir().join(format!("awb-backups-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let db = Database::connect(format!(
+            "sqlite://{}?mode=rwc",
+            dir.join("source.db").display()
+        ))
+        .await
+        .unwrap();
+        db.execute_unprepared("CREATE TABLE quotes (id INTEGER PRIMARY KEY)")
+            .await
+            .unwrap();
+
+        let path = snapshot(&db, &dir.join("backups")).await.unwrap();
+        assert!(path.exists());
+        for old in ["awb-20000101T000000Z.sqlite", "awb-20000102T000000Z.sqlite"] {
+            fs::write(dir.join("backups").join(old), "").unwrap();
+        }
+        fs::write(dir.join("backups").join("notes.txt"), "").unwrap();
+
+        prune_snapshots(&dir.join("backups"), 1).unwrap();
+        let mut remaining = fs::read_dir(dir.join("backups"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        remaining.sort();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(remaining, vec![path, dir.join("backups").join("notes.txt")]);
+    }
+}
@@ -0,0 +1,103 @@
+use std::str::FromStr as _;
+
+use anyhow::Context as _;
+use sea_orm::{QueryOrder as _, prelude::*, sea_query::OnConflict};
+use serenity::all::{ChannelId, GuildId};
+
+use super::Snowflake;
+use crate::settings::Setting;
+
+#[derive(Debug, Clone, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "channel_settings")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub channel_id: Snowflake<ChannelId>,
+    pub guild_id: Snowflake<GuildId>,
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Get a channel's settings, if any are set
+pub(crate) async fn get_entry(
+    db: &DbConn,
+    channel_id: ChannelId,
+) -> Result<Option<Model>, anyhow::Error> {
+    Entity::find()
+        .filter(Column::ChannelId.eq(Snowflake(channel_id)))
+        .one(db)
+        .await
+        .context("Could not get channel settings entry in the database")
+}
+
+/// Get the settings of every channel
+pub(crate) async fn get_all_entries(db: &DbConn) -> Result<Vec<Model>, anyhow::Error> {
+    Entity::find()
+        .order_by_asc(Column::Id)
+        .all(db)
+        .await
+        .context("Could not get channel settings entries in the database")
+}
+
+/// Change a channel's settings, creating the entry if it does not exist
+pub(crate) async fn update_entry(
+    db: &DbConn,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    setting: &Setting,
+    value: Option<String>,
+) -> Result<(), anyhow::Error> {
+    // Use the active value types
+    use sea_orm::ActiveValue::*;
+
+    // Create the entry with the setting
+    let column = Column::from_str(setting.column).context("Unknown settings column")?;
+    let mut entry = ActiveModel {
+        channel_id: Set(channel_id.into()),
+        guild_id: Set(guild_id.into()),
+        ..Default::default()
+    };
+    entry.set(column, sea_orm::Value::String(value.map(Box::new)));
+
+    // Insert the entry, or only change the setting if it already exists
+    Entity::insert(entry)
+        .on_conflict(
+            OnConflict::column(Column::ChannelId)
+                .update_column(column)
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await
+        .context("Could not update channel settings entry in the database")?;
+
+    // Return ok
+    Ok(())
+}
+
+/// Delete a channel's settings
+pub(crate) async fn delete_entry(db: &DbConn, channel_id: ChannelId) -> Result<u64, anyhow::Error> {
+    let result = Entity::delete_many()
+        .filter(Column::ChannelId.eq(Snowflake(channel_id)))
+        .exec(db)
+        .await
+        .context("Could not delete channel settings entry in the database")?;
+    Ok(result.rows_affected)
+}
+
+/// Delete the settings of every channel in a guild
+pub(crate) async fn delete_guild_entries(
+    db: &DbConn,
+    guild_id: GuildId,
+) -> Result<u64, anyhow::Error> {
+    let result = Entity::delete_many()
+        .filter(Column::GuildId.eq(Snowflake(guild_id)))
+        .exec(db)
+        .await
+        .context("Could not delete channel settings entries in the database")?;
+    Ok(result.rows_affected)
+}
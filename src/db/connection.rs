@@ -0,0 +1,144 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Context as _;
+use sea_orm::{ConnectOptions, Database, DbConn, sqlx::sqlite::SqliteJournalMode};
+use tracing::{Level, event};
+
+use crate::config::config;
+
+/// The name of the SQLite database file created in the data directory by default
+const DEFAULT_DATABASE_FILE: &str = "awb.sqlite";
+
+/// Get the directory the bot keeps its data in
+pub(crate) fn data_dir() -> PathBuf {
+    config().data_dir.clone()
+}
+
+/// Get the url of the SQLite database file in a data directory, creating the file if needed
+fn default_database_url(data_dir: &std::path::Path) -> String {
+    format!(
+        "sqlite://{}?mode=rwc",
+        data_dir.join(DEFAULT_DATABASE_FILE).display()
+    )
+}
+
+/// Check if a database url is for a SQLite database that only exists in memory
+pub(crate) fn is_in_memory(database_url: &str) -> bool {
+    database_url.starts_with("sqlite:")
+        && (database_url.contains(":memory:") || database_url.contains("mode=memory"))
+}
+
+/// Use a journal mode on every connection to a SQLite database
+///
+/// Most journal modes only last for the connection they are set on, so they are set as each
+/// connection of the pool is opened.
+fn set_journal_mode(options: &mut ConnectOptions, journal_mode: SqliteJournalMode) {
+    options.map_sqlx_sqlite_opts(move |options| options.journal_mode(journal_mode));
+}
+
+/// Get the url of the database to connect to
+pub(crate) fn database_url() -> Result<String, anyhow::Error> {
+    if let Some(database_url) = &config().database.url {
+        return Ok(database_url.to_owned());
+    }
+
+    // Keep the database in the data directory
+    let data_dir = data_dir();
+    fs::create_dir_all(&data_dir)
+        .with_context(|| format!("Could not create data directory {}", data_dir.display()))?;
+    Ok(default_database_url(&data_dir))
+}
+
+/// Connect to the database
+pub(crate) async fn connect() -> Result<DbConn, anyhow::Error> {
+    // Get the database url
+    let database_url = database_url()?;
+    if is_in_memory(&database_url) {
+        event!(
+            Level::WARN,
+            "The database is in memory, so every quote and setting will be lost when the bot stops"
+        );
+    }
+
+    // Connect to the database
+    let mut options = ConnectOptions::new(&database_url);
+    if let Some(pool_size) = config().database.pool_size {
+        if is_in_memory(&database_url) {
+            // Every connection to an in-memory database would have its own database
+            event!(
+                Level::WARN,
+                "Ignoring the pool size of an in-memory database"
+            );
+        } else {
+            options.max_connections(pool_size);
+        }
+    }
+
+    // Set the journal mode of SQLite database files
+    if database_url.starts_with("sqlite:") && !is_in_memory(&database_url) {
+        let journal_mode = &config().database.sqlite_journal_mode;
+        set_journal_mode(
+            &mut options,
+            journal_mode
+                .parse()
+                .context("Invalid SQLite journal mode")?,
+        );
+        event!(Level::INFO, "Using SQLite journal mode {}", journal_mode);
+    }
+
+    let db = Database::connect(options)
+        .await
+        .context("Failed to connect to database")?;
+
+    // Return the connection
+    Ok(db)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, path::Path};
+
+    use sea_orm::{ConnectionTrait as _, DatabaseBackend, Statement, TransactionTrait as _};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_journal_mode_on_every_connection() {
+        let dir = env::temp_dir().join(format!("awb-journal-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut options = ConnectOptions::new(default_database_url(&dir));
+        options.max_connections(2);
+        set_journal_mode(&mut options, SqliteJournalMode::Truncate);
+        let db = Database::connect(options).await.unwrap();
+
+        // Hold a connection in each transaction, so they can't share one
+        let pragma = Statement::from_string(DatabaseBackend::Sqlite, "PRAGMA journal_mode");
+        let first = db.begin().await.unwrap();
+        let second = db.begin().await.unwrap();
+        for txn in [&first, &second] {
+            let mode = txn
+                .query_one(pragma.clone())
+                .await
+                .unwrap()
+                .unwrap()
+                .try_get_by_index::<String>(0)
+                .unwrap();
+            assert_eq!(mode, "truncate");
+        }
+        drop((first, second));
+        db.close().await.unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_database_urls() {
+        assert_eq!(
+            default_database_url(Path::new("data")),
+            "sqlite://data/awb.sqlite?mode=rwc"
+        );
+        assert!(is_in_memory("sqlite::memory:"));
+        assert!(is_in_memory("sqlite://awb?mode=memory"));
+        assert!(!is_in_memory("sqlite://data/awb.sqlite?mode=rwc"));
+        assert!(!is_in_memory("postgres://localhost/awb"));
+    }
+}
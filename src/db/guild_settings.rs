@@ -1,14 +1,11 @@
+use std::str::FromStr as _;
+
 use anyhow::Context as _;
-use sea_orm::prelude::*;
-
-#[derive(Debug, Clone, Copy, PartialEq, EnumIter, DeriveActiveEnum)]
-#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(2))")]
-pub enum Language {
-    #[sea_orm(string_value = "en")]
-    English,
-    #[sea_orm(string_value = "es")]
-    Spanish,
-}
+use sea_orm::{QueryOrder as _, prelude::*, sea_query::OnConflict};
+use serenity::all::GuildId;
+
+use super::Snowflake;
+use crate::settings::Setting;
 
 #[derive(Debug, Clone, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "guild_settings")]
@@ -16,8 +13,10 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     #[sea_orm(unique)]
-    pub guild_id: String,
-    pub language: Option<Language>,
+    pub guild_id: Snowflake<GuildId>,
+    pub language: Option<String>,
+    pub timezone: Option<String>,
+    pub date_format: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
@@ -25,30 +24,66 @@ pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
 
-/// Create an entry in the guild settings table
-pub(crate) async fn create_entry(
+/// Get a guild's settings, if any are set
+pub(crate) async fn get_entry(
+    db: &DbConn,
+    guild_id: GuildId,
+) -> Result<Option<Model>, anyhow::Error> {
+    Entity::find()
+        .filter(Column::GuildId.eq(Snowflake(guild_id)))
+        .one(db)
+        .await
+        .context("Could not get guild settings entry in the database")
+}
+
+/// Get the settings of every guild
+pub(crate) async fn get_all_entries(db: &DbConn) -> Result<Vec<Model>, anyhow::Error> {
+    Entity::find()
+        .order_by_asc(Column::Id)
+        .all(db)
+        .await
+        .context("Could not get guild settings entries in the database")
+}
+
+/// Change a guild's settings, creating the entry if it does not exist
+pub(crate) async fn update_entry(
     db: &DbConn,
-    guild_id: impl AsRef<str>,
+    guild_id: GuildId,
+    setting: &Setting,
+    value: Option<String>,
 ) -> Result<(), anyhow::Error> {
     // Use the active value types
     use sea_orm::ActiveValue::*;
 
-    // Get guild_id as a string
-    let guild_id = guild_id.as_ref().to_string();
-
-    // Create a new table entry
-    let new_entry = ActiveModel {
-        guild_id: Set(guild_id),
-        language: Set(None),
+    // Create the entry with the setting
+    let column = Column::from_str(setting.column).context("Unknown settings column")?;
+    let mut entry = ActiveModel {
+        guild_id: Set(guild_id.into()),
         ..Default::default()
     };
+    entry.set(column, sea_orm::Value::String(value.map(Box::new)));
 
-    // Insert the new entry into the table
-    new_entry
-        .insert(db)
+    // Insert the entry, or only change the setting if it already exists
+    Entity::insert(entry)
+        .on_conflict(
+            OnConflict::column(Column::GuildId)
+                .update_column(column)
+                .to_owned(),
+        )
+        .exec_without_returning(db)
         .await
-        .context("Could not insert new entry into guild settings table")?;
+        .context("Could not update guild settings entry in the database")?;
 
     // Return ok
     Ok(())
 }
+
+/// Delete a guild's settings
+pub(crate) async fn delete_entry(db: &DbConn, guild_id: GuildId) -> Result<u64, anyhow::Error> {
+    let result = Entity::delete_many()
+        .filter(Column::GuildId.eq(Snowflake(guild_id)))
+        .exec(db)
+        .await
+        .context("Could not delete guild settings entry in the database")?;
+    Ok(result.rows_affected)
+}
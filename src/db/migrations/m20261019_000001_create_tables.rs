@@ -0,0 +1,125 @@
+use sea_orm_migration::prelude::*;
+
+/// Create the tables the bot started with
+///
+/// The tables are only created if they do not exist, so databases created before migrations are
+/// picked up as they are.
+#[derive(DeriveMigrationName)]
+pub(super) struct Migration;
+
+#[derive(DeriveIden)]
+enum GuildSettings {
+    Table,
+    Id,
+    GuildId,
+    Language,
+}
+
+#[derive(DeriveIden)]
+enum UserSettings {
+    Table,
+    Id,
+    UserId,
+    Language,
+}
+
+#[derive(DeriveIden)]
+enum Quotebook {
+    Table,
+    Id,
+    MessageId,
+    GuildId,
+    AuthorId,
+    Datetime,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create guild_settings table
+        manager
+            .create_table(
+                Table::create()
+                    .table(GuildSettings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GuildSettings::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(GuildSettings::GuildId)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(GuildSettings::Language).string_len(2))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create user_settings table
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserSettings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UserSettings::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(UserSettings::UserId)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(UserSettings::Language).string_len(2))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create quotebook table
+        manager
+            .create_table(
+                Table::create()
+                    .table(Quotebook::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Quotebook::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Quotebook::MessageId)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Quotebook::GuildId).string().not_null())
+                    .col(ColumnDef::new(Quotebook::AuthorId).string().not_null())
+                    .col(ColumnDef::new(Quotebook::Datetime).date_time().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Quotebook::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(UserSettings::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(GuildSettings::Table).to_owned())
+            .await
+    }
+}
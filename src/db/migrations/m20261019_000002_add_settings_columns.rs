@@ -0,0 +1,204 @@
+use sea_orm_migration::{prelude::*, sea_orm::DatabaseBackend};
+
+/// Add the timezone and date format settings, the channel settings and longer language tags
+#[derive(DeriveMigrationName)]
+pub(super) struct Migration;
+
+/// The length of the language columns, which hold locale tags like es-419
+const LANGUAGE_LENGTH: u32 = 16;
+
+/// The language codes stored before languages were locale tags, and the tags they became
+const LANGUAGE_TAGS: [(&str, &str); 2] = [("en", "en-US"), ("es", "es-419")];
+
+#[derive(Clone, Copy, DeriveIden)]
+enum GuildSettings {
+    Table,
+    Language,
+    Timezone,
+    DateFormat,
+}
+
+#[derive(Clone, Copy, DeriveIden)]
+enum UserSettings {
+    Table,
+    Language,
+    Timezone,
+    DateFormat,
+}
+
+#[derive(Clone, Copy, DeriveIden)]
+enum ChannelSettings {
+    Table,
+    Id,
+    ChannelId,
+    GuildId,
+    Language,
+}
+
+/// Add a nullable string column to a table if it does not have it yet
+async fn add_column(
+    manager: &SchemaManager<'_>,
+    table: impl IntoIden + Clone + 'static,
+    column: impl IntoIden + Clone + 'static,
+) -> Result<(), DbErr> {
+    let table_name = table.clone().into_iden().to_string();
+    let column_name = column.clone().into_iden().to_string();
+    if manager.has_column(&table_name, &column_name).await? {
+        return Ok(());
+    }
+    manager
+        .alter_table(
+            Table::alter()
+                .table(table)
+                .add_column(ColumnDef::new(column).string())
+                .to_owned(),
+        )
+        .await
+}
+
+/// Remove a column from a table if it has it
+async fn drop_column(
+    manager: &SchemaManager<'_>,
+    table: impl IntoIden + Clone + 'static,
+    column: impl IntoIden + Clone + 'static,
+) -> Result<(), DbErr> {
+    let table_name = table.clone().into_iden().to_string();
+    let column_name = column.clone().into_iden().to_string();
+    if !manager.has_column(&table_name, &column_name).await? {
+        return Ok(());
+    }
+    manager
+        .alter_table(Table::alter().table(table).drop_column(column).to_owned())
+        .await
+}
+
+/// Replace the values of the language column of a table
+async fn replace_languages(
+    manager: &SchemaManager<'_>,
+    table: impl IntoIden + Clone + 'static,
+    column: impl IntoIden + Clone + 'static,
+    replacements: impl IntoIterator<Item = (&str, &str)>,
+) -> Result<(), DbErr> {
+    for (from, to) in replacements {
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(table.clone())
+                    .value(column.clone(), to)
+                    .and_where(Expr::col(column.clone()).eq(from))
+                    .to_owned(),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Change the length of the language column of a table
+async fn resize_language(
+    manager: &SchemaManager<'_>,
+    table: impl IntoIden + 'static,
+    column: impl IntoIden + 'static,
+    length: u32,
+) -> Result<(), DbErr> {
+    // SQLite does not enforce the length of strings, and can not modify columns
+    if manager.get_database_backend() == DatabaseBackend::Sqlite {
+        return Ok(());
+    }
+    manager
+        .alter_table(
+            Table::alter()
+                .table(table)
+                .modify_column(ColumnDef::new(column).string_len(length))
+                .to_owned(),
+        )
+        .await
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add the timezone and date format columns
+        add_column(manager, GuildSettings::Table, GuildSettings::Timezone).await?;
+        add_column(manager, GuildSettings::Table, GuildSettings::DateFormat).await?;
+        add_column(manager, UserSettings::Table, UserSettings::Timezone).await?;
+        add_column(manager, UserSettings::Table, UserSettings::DateFormat).await?;
+
+        // Make the language columns fit locale tags
+        resize_language(
+            manager,
+            GuildSettings::Table,
+            GuildSettings::Language,
+            LANGUAGE_LENGTH,
+        )
+        .await?;
+        resize_language(
+            manager,
+            UserSettings::Table,
+            UserSettings::Language,
+            LANGUAGE_LENGTH,
+        )
+        .await?;
+
+        // Store the languages as locale tags
+        replace_languages(
+            manager,
+            GuildSettings::Table,
+            GuildSettings::Language,
+            LANGUAGE_TAGS,
+        )
+        .await?;
+        replace_languages(
+            manager,
+            UserSettings::Table,
+            UserSettings::Language,
+            LANGUAGE_TAGS,
+        )
+        .await?;
+
+        // Create channel_settings table
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChannelSettings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ChannelSettings::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ChannelSettings::ChannelId)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(ChannelSettings::GuildId).string().not_null())
+                    .col(ColumnDef::new(ChannelSettings::Language).string_len(LANGUAGE_LENGTH))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ChannelSettings::Table).to_owned())
+            .await?;
+        let codes = LANGUAGE_TAGS.map(|(code, tag)| (tag, code));
+        replace_languages(manager, UserSettings::Table, UserSettings::Language, codes).await?;
+        replace_languages(
+            manager,
+            GuildSettings::Table,
+            GuildSettings::Language,
+            codes,
+        )
+        .await?;
+        resize_language(manager, UserSettings::Table, UserSettings::Language, 2).await?;
+        resize_language(manager, GuildSettings::Table, GuildSettings::Language, 2).await?;
+        drop_column(manager, UserSettings::Table, UserSettings::DateFormat).await?;
+        drop_column(manager, UserSettings::Table, UserSettings::Timezone).await?;
+        drop_column(manager, GuildSettings::Table, GuildSettings::DateFormat).await?;
+        drop_column(manager, GuildSettings::Table, GuildSettings::Timezone).await
+    }
+}
@@ -0,0 +1,269 @@
+use sea_orm_migration::{prelude::*, sea_orm::DatabaseBackend};
+
+/// Store Discord ids as 64-bit integers instead of strings, and index the columns they are
+/// searched by
+#[derive(DeriveMigrationName)]
+pub(super) struct Migration;
+
+/// The length of the language columns, which hold locale tags like es-419
+const LANGUAGE_LENGTH: u32 = 16;
+
+/// The suffix of the tables that SQLite tables are rebuilt into
+const REBUILD_SUFFIX: &str = "_rebuild";
+
+#[derive(Clone, Copy, DeriveIden)]
+enum Quotebook {
+    Table,
+    GuildId,
+    AuthorId,
+}
+
+#[derive(Clone, Copy, DeriveIden)]
+enum ChannelSettings {
+    Table,
+    GuildId,
+}
+
+/// The type Discord ids are stored as
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum IdType {
+    Text,
+    Integer,
+}
+
+impl IdType {
+    /// Create the definition of an id column
+    fn column(self, name: &'static str) -> ColumnDef {
+        let mut column = ColumnDef::new(Alias::new(name));
+        match self {
+            Self::Text => column.string(),
+            Self::Integer => column.big_integer(),
+        };
+        column.not_null().to_owned()
+    }
+
+    /// Get the name of the type in a database backend
+    fn sql_type(self, backend: DatabaseBackend) -> &'static str {
+        match (self, backend) {
+            (Self::Text, DatabaseBackend::Sqlite) => "TEXT",
+            (Self::Text, DatabaseBackend::MySql) => "VARCHAR(255)",
+            (Self::Text, DatabaseBackend::Postgres) => "VARCHAR",
+            (Self::Integer, DatabaseBackend::Sqlite) => "INTEGER",
+            (Self::Integer, _) => "BIGINT",
+        }
+    }
+}
+
+/// A table with Discord id columns
+struct SnowflakeTable {
+    name: &'static str,
+    /// The columns holding Discord ids
+    ids: &'static [&'static str],
+    /// Every column of the table
+    columns: &'static [&'static str],
+    /// Create the table with a name and the type of its id columns
+    create: fn(&str, IdType) -> TableCreateStatement,
+}
+
+/// Create a table with an auto incrementing primary key
+fn create_table(name: &str, id: ColumnDef) -> TableCreateStatement {
+    Table::create().table(Alias::new(name)).col(id).to_owned()
+}
+
+/// Create the primary key column
+fn primary_key(big: bool) -> ColumnDef {
+    let mut column = ColumnDef::new(Alias::new("id"));
+    if big {
+        column.big_integer();
+    } else {
+        column.integer();
+    }
+    column.not_null().auto_increment().primary_key().to_owned()
+}
+
+const TABLES: [SnowflakeTable; 4] = [
+    SnowflakeTable {
+        name: "guild_settings",
+        ids: &["guild_id"],
+        columns: &["id", "guild_id", "language", "timezone", "date_format"],
+        create: |name, id_type| {
+            create_table(name, primary_key(false))
+                .col(id_type.column("guild_id").unique_key())
+                .col(ColumnDef::new(Alias::new("language")).string_len(LANGUAGE_LENGTH))
+                .col(ColumnDef::new(Alias::new("timezone")).string())
+                .col(ColumnDef::new(Alias::new("date_format")).string())
+                .to_owned()
+        },
+    },
+    SnowflakeTable {
+        name: "user_settings",
+        ids: &["user_id"],
+        columns: &["id", "user_id", "language", "timezone", "date_format"],
+        create: |name, id_type| {
+            create_table(name, primary_key(false))
+                .col(id_type.column("user_id").unique_key())
+                .col(ColumnDef::new(Alias::new("language")).string_len(LANGUAGE_LENGTH))
+                .col(ColumnDef::new(Alias::new("timezone")).string())
+                .col(ColumnDef::new(Alias::new("date_format")).string())
+                .to_owned()
+        },
+    },
+    SnowflakeTable {
+        name: "channel_settings",
+        ids: &["channel_id", "guild_id"],
+        columns: &["id", "channel_id", "guild_id", "language"],
+        create: |name, id_type| {
+            create_table(name, primary_key(false))
+                .col(id_type.column("channel_id").unique_key())
+                .col(id_type.column("guild_id"))
+                .col(ColumnDef::new(Alias::new("language")).string_len(LANGUAGE_LENGTH))
+                .to_owned()
+        },
+    },
+    SnowflakeTable {
+        name: "quotebook",
+        ids: &["message_id", "guild_id", "author_id"],
+        columns: &["id", "message_id", "guild_id", "author_id", "datetime"],
+        create: |name, id_type| {
+            create_table(name, primary_key(true))
+                .col(id_type.column("message_id").unique_key())
+                .col(id_type.column("guild_id"))
+                .col(id_type.column("author_id"))
+                .col(
+                    ColumnDef::new(Alias::new("datetime"))
+                        .date_time()
+                        .not_null(),
+                )
+                .to_owned()
+        },
+    },
+];
+
+/// Change the type of the id columns of a table
+async fn convert_ids(
+    manager: &SchemaManager<'_>,
+    table: &SnowflakeTable,
+    id_type: IdType,
+) -> Result<(), DbErr> {
+    let backend = manager.get_database_backend();
+    let db = manager.get_connection();
+    let sql_type = id_type.sql_type(backend);
+    match backend {
+        DatabaseBackend::Postgres => {
+            for id in table.ids {
+                db.execute_unprepared(&format!(
+                    r#"ALTER TABLE "{table}" ALTER COLUMN "{id}" TYPE {sql_type} USING "{id}"::{sql_type}"#,
+                    table = table.name,
+                ))
+                .await?;
+            }
+        }
+        DatabaseBackend::MySql => {
+            for id in table.ids {
+                db.execute_unprepared(&format!(
+                    "ALTER TABLE `{}` MODIFY COLUMN `{}` {} NOT NULL",
+                    table.name, id, sql_type
+                ))
+                .await?;
+            }
+        }
+        DatabaseBackend::Sqlite => {
+            // SQLite can not change the type of a column, so copy the table into a new one
+            let rebuild = format!("{}{}", table.name, REBUILD_SUFFIX);
+            manager
+                .create_table((table.create)(&rebuild, id_type))
+                .await?;
+            let columns = table
+                .columns
+                .iter()
+                .map(|column| format!(r#""{}""#, column))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let values = table
+                .columns
+                .iter()
+                .map(|column| {
+                    if table.ids.contains(column) {
+                        format!(r#"CAST("{}" AS {})"#, column, sql_type)
+                    } else {
+                        format!(r#""{}""#, column)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            db.execute_unprepared(&format!(
+                r#"INSERT INTO "{}" ({}) SELECT {} FROM "{}""#,
+                rebuild, columns, values, table.name
+            ))
+            .await?;
+            manager
+                .drop_table(Table::drop().table(Alias::new(table.name)).to_owned())
+                .await?;
+            manager
+                .rename_table(
+                    Table::rename()
+                        .table(Alias::new(rebuild), Alias::new(table.name))
+                        .to_owned(),
+                )
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Convert the id columns to integers
+        for table in &TABLES {
+            convert_ids(manager, table, IdType::Integer).await?;
+        }
+
+        // Index the columns quotes and channel settings are searched by
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-quotebook-guild_id-author_id")
+                    .table(Quotebook::Table)
+                    .col(Quotebook::GuildId)
+                    .col(Quotebook::AuthorId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-channel_settings-guild_id")
+                    .table(ChannelSettings::Table)
+                    .col(ChannelSettings::GuildId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Remove the indexes
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-channel_settings-guild_id")
+                    .table(ChannelSettings::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-quotebook-guild_id-author_id")
+                    .table(Quotebook::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Convert the id columns back to strings
+        for table in &TABLES {
+            convert_ids(manager, table, IdType::Text).await?;
+        }
+        Ok(())
+    }
+}
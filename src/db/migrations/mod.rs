@@ -0,0 +1,138 @@
+use sea_orm_migration::prelude::*;
+
+mod m20261019_000001_create_tables;
+mod m20261019_000002_add_settings_columns;
+mod m20261019_000003_snowflake_columns;
+
+/// Runs the migrations of the database schema
+pub(crate) struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            Box::new(m20261019_000001_create_tables::Migration),
+            Box::new(m20261019_000002_add_settings_columns::Migration),
+            Box::new(m20261019_000003_snowflake_columns::Migration),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{ConnectionTrait as _, Database, DatabaseBackend, EntityTrait as _, Statement};
+    use serenity::all::{GuildId, MessageId};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_migrations_up_and_down() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        Migrator::up(&db, None).await.unwrap();
+
+        let manager = SchemaManager::new(&db);
+        for table in [
+            "guild_settings",
+            "user_settings",
+            "channel_settings",
+            "quotebook",
+        ] {
+            assert!(manager.has_table(table).await.unwrap(), "{}", table);
+        }
+        assert!(
+            Migrator::get_pending_migrations(&db)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        Migrator::down(&db, None).await.unwrap();
+        assert!(!manager.has_table("guild_settings").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_settings_columns_added_to_old_tables() {
+        // Create the tables like the bot did before migrations
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        for table in ["guild_settings", "user_settings"] {
+            let id = table.replace("settings", "id");
+            db.execute_unprepared(&format!(
+                "CREATE TABLE {table} (id integer NOT NULL PRIMARY KEY AUTOINCREMENT, \
+                 {id} varchar NOT NULL UNIQUE, language varchar(2))"
+            ))
+            .await
+            .unwrap();
+        }
+        db.execute_unprepared(
+            "INSERT INTO user_settings (user_id, language) VALUES ('1235772100384526377', 'es')",
+        )
+        .await
+        .unwrap();
+        db.execute_unprepared("INSERT INTO guild_settings (guild_id, language) VALUES ('2', 'en')")
+            .await
+            .unwrap();
+
+        // The timezone and date format columns are added without losing settings
+        Migrator::up(&db, None).await.unwrap();
+        let manager = SchemaManager::new(&db);
+        for table in ["guild_settings", "user_settings"] {
+            for column in ["timezone", "date_format"] {
+                assert!(
+                    manager.has_column(table, column).await.unwrap(),
+                    "{}.{}",
+                    table,
+                    column
+                );
+            }
+        }
+        let settings = crate::db::user_settings::Entity::find()
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(settings.timezone, None);
+
+        // The languages are converted to locale tags, and back when rolled back
+        assert_eq!(settings.language.as_deref(), Some("es-419"));
+        let guild = crate::db::guild_settings::Entity::find()
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(guild.language.as_deref(), Some("en-US"));
+        Migrator::down(&db, Some(2)).await.unwrap();
+        let language = db
+            .query_one(Statement::from_string(
+                DatabaseBackend::Sqlite,
+                "SELECT language FROM user_settings",
+            ))
+            .await
+            .unwrap()
+            .unwrap()
+            .try_get::<String>("", "language")
+            .unwrap();
+        assert_eq!(language, "es");
+    }
+
+    #[tokio::test]
+    async fn test_snowflake_columns_keep_ids() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        Migrator::up(&db, Some(2)).await.unwrap();
+        db.execute_unprepared(
+            "INSERT INTO quotebook (message_id, guild_id, author_id, datetime) \
+             VALUES ('1300000000000000000', '1235772100384526377', '42', '2026-10-19 12:00:00')",
+        )
+        .await
+        .unwrap();
+
+        Migrator::up(&db, None).await.unwrap();
+        let quote = crate::db::quotebook::Entity::find()
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(quote.message_id.0, MessageId::new(1300000000000000000));
+        assert_eq!(quote.guild_id.0, GuildId::new(1235772100384526377));
+        assert_eq!(quote.author_id.0.get(), 42);
+    }
+}
@@ -1,42 +1,26 @@
 use anyhow::Context as _;
-use sea_orm::{DatabaseBackend, Schema, prelude::*};
+use sea_orm::prelude::*;
+use sea_orm_migration::MigratorTrait as _;
 
+pub(crate) use backup::spawn_snapshots;
+pub(crate) use connection::connect;
+pub(crate) use migrations::Migrator;
+pub(crate) use snowflake::Snowflake;
+
+mod backup;
+pub(crate) mod channel_settings;
+mod connection;
 pub(crate) mod guild_settings;
+mod migrations;
 pub(crate) mod quotebook;
+mod snowflake;
+#[cfg(test)]
+mod tests;
 pub(crate) mod user_settings;
 
-/// Setup the schema of the database
+/// Setup the schema of the database by applying every pending migration
 pub(crate) async fn setup_schema(db: &DbConn) -> Result<(), anyhow::Error> {
-    // Create the schema
-    let schema = Schema::new(DatabaseBackend::Sqlite);
-
-    // Create guild_settings table
-    let stmt = schema
-        .create_table_from_entity(guild_settings::Entity)
-        .if_not_exists()
-        .take();
-    db.execute(db.get_database_backend().build(&stmt))
-        .await
-        .context("Failed to create guild_settings table")?;
-
-    // Create user_settings table
-    let stmt = schema
-        .create_table_from_entity(user_settings::Entity)
-        .if_not_exists()
-        .take();
-    db.execute(db.get_database_backend().build(&stmt))
+    Migrator::up(db, None)
         .await
-        .context("Failed to create user_settings table")?;
-
-    // Create quotebook table
-    let stmt = schema
-        .create_table_from_entity(quotebook::Entity)
-        .if_not_exists()
-        .take();
-    db.execute(db.get_database_backend().build(&stmt))
-        .await
-        .context("Failed to create quotebook table")?;
-
-    // Return ok
-    Ok(())
+        .context("Failed to apply migrations")
 }
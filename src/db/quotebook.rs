@@ -1,6 +1,9 @@
 use anyhow::{Context, anyhow};
 use chrono::NaiveDateTime;
 use sea_orm::{QueryOrder as _, QuerySelect as _, prelude::*};
+use serenity::all::{GuildId, MessageId, UserId};
+
+use super::Snowflake;
 
 #[derive(Debug, Clone, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "quotebook")]
@@ -8,9 +11,9 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i64,
     #[sea_orm(unique)]
-    pub message_id: String,
-    pub guild_id: String,
-    pub author_id: String,
+    pub message_id: Snowflake<MessageId>,
+    pub guild_id: Snowflake<GuildId>,
+    pub author_id: Snowflake<UserId>,
     pub datetime: NaiveDateTime,
 }
 
@@ -22,24 +25,19 @@ impl ActiveModelBehavior for ActiveModel {}
 /// Create an entry in the quotebook table
 pub(crate) async fn create_entry(
     db: &DbConn,
-    message_id: impl AsRef<str>,
-    guild_id: impl AsRef<str>,
-    author_id: impl AsRef<str>,
+    message_id: MessageId,
+    guild_id: GuildId,
+    author_id: UserId,
     datetime: NaiveDateTime,
 ) -> Result<(), anyhow::Error> {
     // Use the active value types
     use sea_orm::ActiveValue::*;
 
-    // Get ids as strings
-    let message_id = message_id.as_ref().to_string();
-    let guild_id = guild_id.as_ref().to_string();
-    let author_id = author_id.as_ref().to_string();
-
     // Create a new table entry
     let new_entry = ActiveModel {
-        message_id: Set(message_id),
-        guild_id: Set(guild_id),
-        author_id: Set(author_id),
+        message_id: Set(message_id.into()),
+        guild_id: Set(guild_id.into()),
+        author_id: Set(author_id.into()),
         datetime: Set(datetime),
         ..Default::default()
     };
@@ -54,11 +52,14 @@ pub(crate) async fn create_entry(
     Ok(())
 }
 
+/// How many entries are returned when no limit is given
+const DEFAULT_LIMIT: u8 = 5;
+
 #[derive(Debug, Clone)]
 pub(crate) struct EntryFilters {
-    _message_id: Option<String>,
-    _guild_id: Option<String>,
-    _author_id: Option<String>,
+    _message_id: Option<MessageId>,
+    _guild_id: Option<GuildId>,
+    _author_id: Option<UserId>,
     _datetime_start: Option<NaiveDateTime>,
     _datetime_end: Option<NaiveDateTime>,
     _limit: Option<u8>,
@@ -77,18 +78,18 @@ impl EntryFilters {
         }
     }
 
-    pub(crate) fn message_id(mut self, message_id: impl AsRef<str>) -> Self {
-        self._message_id = Some(message_id.as_ref().to_string());
+    pub(crate) fn message_id(mut self, message_id: MessageId) -> Self {
+        self._message_id = Some(message_id);
         self
     }
 
-    pub(crate) fn guild_id(mut self, guild_id: impl AsRef<str>) -> Self {
-        self._guild_id = Some(guild_id.as_ref().to_string());
+    pub(crate) fn guild_id(mut self, guild_id: GuildId) -> Self {
+        self._guild_id = Some(guild_id);
         self
     }
 
-    pub(crate) fn author_id(mut self, author_id: impl AsRef<str>) -> Self {
-        self._author_id = Some(author_id.as_ref().to_string());
+    pub(crate) fn author_id(mut self, author_id: UserId) -> Self {
+        self._author_id = Some(author_id);
         self
     }
 
@@ -106,6 +107,32 @@ impl EntryFilters {
         self._limit = Some(limit);
         self
     }
+
+    /// Get the most entries to return
+    pub(crate) fn max_entries(&self) -> u8 {
+        self._limit.unwrap_or(DEFAULT_LIMIT)
+    }
+
+    /// Check if an entry matches the filters
+    pub(crate) fn matches(&self, entry: &Model) -> bool {
+        if self
+            ._guild_id
+            .is_some_and(|guild_id| entry.guild_id.0 != guild_id)
+        {
+            return false;
+        }
+        if let Some(message_id) = self._message_id {
+            return entry.message_id.0 == message_id;
+        }
+        self._author_id
+            .is_none_or(|author_id| entry.author_id.0 == author_id)
+            && self
+                ._datetime_start
+                .is_none_or(|datetime| entry.datetime >= datetime)
+            && self
+                ._datetime_end
+                .is_none_or(|datetime| entry.datetime <= datetime)
+    }
 }
 
 impl Default for EntryFilters {
@@ -136,15 +163,15 @@ pub(crate) async fn get_entries(
 
     // Add each filter if it exists
     if let Some(guild_id) = &filters._guild_id {
-        query = query.filter(GuildId.eq(guild_id));
+        query = query.filter(GuildId.eq(Snowflake(*guild_id)));
     } else {
         return Err(anyhow!("No guild ID provided")).context("No guild ID provided");
     }
     if let Some(message_id) = &filters._message_id {
-        query = query.filter(MessageId.eq(message_id));
+        query = query.filter(MessageId.eq(Snowflake(*message_id)));
     } else {
         if let Some(author_id) = &filters._author_id {
-            query = query.filter(AuthorId.eq(author_id));
+            query = query.filter(AuthorId.eq(Snowflake(*author_id)));
         }
         if let Some(datetime_start) = &filters._datetime_start {
             query = query.filter(Datetime.gte(datetime_start.to_owned()));
@@ -155,14 +182,10 @@ pub(crate) async fn get_entries(
     }
 
     // Add the limit
-    if let Some(limit) = &filters._limit {
-        query = query.limit(*limit as u64);
-    } else {
-        query = query.limit(5);
-    }
+    query = query.limit(filters.max_entries() as u64);
 
-    // Order by the date
-    query = query.order_by_desc(Datetime);
+    // Order by the message ids, which are ordered by when the messages were sent
+    query = query.order_by_desc(MessageId);
 
     // Execute the query
     let entries = query
@@ -173,3 +196,51 @@ pub(crate) async fn get_entries(
     // Return the entries
     Ok(entries)
 }
+
+/// Get every entry in the quotebook table with messages from an author, in any guild
+pub(crate) async fn get_author_entries(
+    db: &DbConn,
+    author_id: UserId,
+) -> Result<Vec<Model>, anyhow::Error> {
+    Entity::find()
+        .filter(Column::AuthorId.eq(Snowflake(author_id)))
+        .order_by_asc(Column::MessageId)
+        .all(db)
+        .await
+        .context("Could not get database entries in quotebook table")
+}
+
+/// Get every entry in the quotebook table, in the order they were saved
+pub(crate) async fn get_all_entries(db: &DbConn) -> Result<Vec<Model>, anyhow::Error> {
+    Entity::find()
+        .order_by_asc(Column::Id)
+        .all(db)
+        .await
+        .context("Could not get database entries in quotebook table")
+}
+
+/// Delete every entry in the quotebook table with messages from an author
+pub(crate) async fn delete_author_entries(
+    db: &DbConn,
+    author_id: UserId,
+) -> Result<u64, anyhow::Error> {
+    let result = Entity::delete_many()
+        .filter(Column::AuthorId.eq(Snowflake(author_id)))
+        .exec(db)
+        .await
+        .context("Could not delete database entries in quotebook table")?;
+    Ok(result.rows_affected)
+}
+
+/// Delete every entry in the quotebook table from a guild
+pub(crate) async fn delete_guild_entries(
+    db: &DbConn,
+    guild_id: GuildId,
+) -> Result<u64, anyhow::Error> {
+    let result = Entity::delete_many()
+        .filter(Column::GuildId.eq(Snowflake(guild_id)))
+        .exec(db)
+        .await
+        .context("Could not delete database entries in quotebook table")?;
+    Ok(result.rows_affected)
+}
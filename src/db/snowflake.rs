@@ -0,0 +1,99 @@
+use std::num::NonZeroU64;
+
+use sea_orm::{
+    ColIdx, DbErr, QueryResult, TryGetError, TryGetable,
+    sea_query::{ArrayType, ColumnType, Nullable, Value, ValueType, ValueTypeErr},
+};
+
+/// A Discord id, like a `GuildId` or `MessageId`, stored as a 64-bit integer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct Snowflake<T>(pub T);
+
+impl<T> Snowflake<T>
+where
+    T: From<NonZeroU64>,
+{
+    /// Get the id from the integer it is stored as
+    fn from_i64(value: i64) -> Option<Self> {
+        <u64 as TryFrom<i64>>::try_from(value)
+            .ok()
+            .and_then(NonZeroU64::new)
+            .map(|id| Self(id.into()))
+    }
+}
+
+impl<T> From<T> for Snowflake<T> {
+    fn from(id: T) -> Self {
+        Self(id)
+    }
+}
+
+impl<T> From<Snowflake<T>> for Value
+where
+    T: Into<i64>,
+{
+    fn from(snowflake: Snowflake<T>) -> Self {
+        Value::BigInt(Some(snowflake.0.into()))
+    }
+}
+
+impl<T> TryGetable for Snowflake<T>
+where
+    T: From<NonZeroU64>,
+{
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value = i64::try_get_by(res, index)?;
+        Self::from_i64(value)
+            .ok_or_else(|| TryGetError::DbErr(DbErr::Type(format!("Invalid Discord id {}", value))))
+    }
+}
+
+impl<T> ValueType for Snowflake<T>
+where
+    T: From<NonZeroU64>,
+{
+    fn try_from(value: Value) -> Result<Self, ValueTypeErr> {
+        match value {
+            Value::BigInt(Some(value)) => Self::from_i64(value).ok_or(ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        std::any::type_name::<Self>().to_owned()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::BigInt
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::BigInteger
+    }
+}
+
+impl<T> Nullable for Snowflake<T> {
+    fn null() -> Value {
+        Value::BigInt(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::GuildId;
+
+    use super::*;
+
+    #[test]
+    fn test_snowflake_values() {
+        let guild_id = GuildId::new(1235772100384526377);
+        let value = Value::from(Snowflake(guild_id));
+        assert_eq!(value, Value::BigInt(Some(1235772100384526377)));
+        assert_eq!(
+            <Snowflake<GuildId> as ValueType>::try_from(value).ok(),
+            Some(Snowflake(guild_id))
+        );
+        assert!(<Snowflake<GuildId> as ValueType>::try_from(Value::BigInt(Some(0))).is_err());
+        assert!(<Snowflake<GuildId> as ValueType>::try_from(Value::BigInt(Some(-1))).is_err());
+    }
+}
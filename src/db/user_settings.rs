@@ -1,16 +1,11 @@
+use std::str::FromStr as _;
+
 use anyhow::Context as _;
-use async_recursion::async_recursion;
-use sea_orm::{IntoActiveModel as _, prelude::*};
+use sea_orm::{QueryOrder as _, prelude::*, sea_query::OnConflict};
 use serenity::all::UserId;
 
-#[derive(Debug, Clone, Copy, PartialEq, EnumIter, DeriveActiveEnum)]
-#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(2))")]
-pub enum Language {
-    #[sea_orm(string_value = "en")]
-    English,
-    #[sea_orm(string_value = "es")]
-    Spanish,
-}
+use super::Snowflake;
+use crate::settings::Setting;
 
 #[derive(Debug, Clone, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "user_settings")]
@@ -18,8 +13,10 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     #[sea_orm(unique)]
-    pub user_id: String,
-    pub language: Option<Language>,
+    pub user_id: Snowflake<UserId>,
+    pub language: Option<String>,
+    pub timezone: Option<String>,
+    pub date_format: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -27,64 +24,66 @@ pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
 
-/// Create an entry in the user settings table
-pub(crate) async fn create_entry(
+/// Get a user's settings, if any are set
+pub(crate) async fn get_entry(
     db: &DbConn,
-    user_id: impl AsRef<str>,
+    user_id: UserId,
+) -> Result<Option<Model>, anyhow::Error> {
+    Entity::find()
+        .filter(Column::UserId.eq(Snowflake(user_id)))
+        .one(db)
+        .await
+        .context("Could not get user settings entry in the database")
+}
+
+/// Get the settings of every user
+pub(crate) async fn get_all_entries(db: &DbConn) -> Result<Vec<Model>, anyhow::Error> {
+    Entity::find()
+        .order_by_asc(Column::Id)
+        .all(db)
+        .await
+        .context("Could not get user settings entries in the database")
+}
+
+/// Change a user's settings, creating the entry if it does not exist
+pub(crate) async fn update_entry(
+    db: &DbConn,
+    user_id: UserId,
+    setting: &Setting,
+    value: Option<String>,
 ) -> Result<(), anyhow::Error> {
     // Use the active value types
     use sea_orm::ActiveValue::*;
 
-    // Get user_id as a string
-    let user_id = user_id.as_ref().to_string();
-
-    // Create a new entry
-    let new_entry = ActiveModel {
-        user_id: Set(user_id),
-        language: Set(None),
+    // Create the entry with the setting
+    let column = Column::from_str(setting.column).context("Unknown settings column")?;
+    let mut entry = ActiveModel {
+        user_id: Set(user_id.into()),
         ..Default::default()
     };
+    entry.set(column, sea_orm::Value::String(value.map(Box::new)));
 
-    // Insert the new entry into the database
-    new_entry
-        .insert(db)
+    // Insert the entry, or only change the setting if it already exists
+    Entity::insert(entry)
+        .on_conflict(
+            OnConflict::column(Column::UserId)
+                .update_column(column)
+                .to_owned(),
+        )
+        .exec_without_returning(db)
         .await
-        .context("Could not insert new entry into user settings table")?;
+        .context("Could not update user settings entry in the database")?;
 
     // Return ok
     Ok(())
 }
 
-/// Change a user's settings
-#[async_recursion]
-pub(crate) async fn update_entry<V>(
-    db: &DbConn,
-    user_id: UserId,
-    column: Column,
-    value: V,
-) -> Result<(), anyhow::Error>
-where
-    V: Into<sea_orm::Value> + Send,
-{
-    let value = value.into();
-    let user_id_ = user_id.to_string();
-    let model = Entity::find()
-        .filter(Column::UserId.eq(&user_id_))
-        .one(db)
+/// Delete a user's settings
+pub(crate) async fn delete_entry(db: &DbConn, user_id: UserId) -> Result<u64, anyhow::Error> {
+    let result = Entity::delete_many()
+        .filter(Column::UserId.eq(Snowflake(user_id)))
+        .exec(db)
         .await
-        .context("Could not get user settings entry in the database")?;
-    if let Some(model) = model {
-        let mut model = model.into_active_model();
-        model.set(column, value);
-        model
-            .update(db)
-            .await
-            .context("Could not update user settings entry in the database")?;
-    } else {
-        create_entry(db, &user_id_)
-            .await
-            .context("Could not create user settings entry in the database")?;
-        update_entry(db, user_id, column, value).await?;
-    }
-    Ok(())
+        .context("Could not delete user settings entry in the database")?;
+    Ok(result.rows_affected)
 }
@@ -0,0 +1,328 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use serde_yaml::Value;
+
+use crate::settings::{ChoiceLabel, SETTINGS};
+
+use super::{
+    Language,
+    commands::{COMMANDS_KEY, is_valid_name},
+    language::DEFAULT_LANGUAGE_TAG,
+    template::{META_KEY, flatten},
+};
+
+/// The macro whose literal keys are checked in the source, split so this file does not match itself
+const MACRO_CALL: &str = concat!("localize_message", "!(");
+
+/// A problem found in the translations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TranslationIssue {
+    MissingKey {
+        file: String,
+        key: String,
+    },
+    ExtraKey {
+        file: String,
+        key: String,
+    },
+    PlaceholderMismatch {
+        file: String,
+        key: String,
+        expected: BTreeSet<String>,
+        found: BTreeSet<String>,
+    },
+    UnknownKey {
+        location: String,
+        key: String,
+    },
+    InvalidLanguage {
+        file: String,
+        error: String,
+    },
+    InvalidCommandName {
+        file: String,
+        key: String,
+        name: String,
+    },
+}
+
+impl std::fmt::Display for TranslationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingKey { file, key } => write!(f, "{}: missing key {}", file, key),
+            Self::ExtraKey { file, key } => write!(f, "{}: extra key {}", file, key),
+            Self::PlaceholderMismatch {
+                file,
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}: key {} has placeholders {:?} but expected {:?}",
+                file, key, found, expected
+            ),
+            Self::UnknownKey { location, key } => {
+                write!(f, "{}: key {} is not in any translations", location, key)
+            }
+            Self::InvalidLanguage { file, error } => {
+                write!(f, "{}: invalid language metadata: {}", file, error)
+            }
+            Self::InvalidCommandName { file, key, name } => write!(
+                f,
+                "{}: key {} has `{}`, which Discord does not accept as a name",
+                file, key, name
+            ),
+        }
+    }
+}
+
+/// Find the command and parameter names in the commands section of a translations file
+fn command_names(value: &Value, prefix: &str, names: &mut Vec<(String, String)>) {
+    let Value::Mapping(mapping) = value else {
+        return;
+    };
+    for (component, value) in mapping {
+        let Some(component) = component.as_str() else {
+            continue;
+        };
+        let key = [prefix, component].join(".");
+        match value {
+            Value::String(name) if component == "name" => names.push((key, name.to_owned())),
+            value => command_names(value, &key, names),
+        }
+    }
+}
+
+/// Find the literal keys given to the localization macro in a source file
+fn source_keys(source: &str) -> Vec<(usize, String)> {
+    let mut keys = Vec::new();
+    let mut rest = source;
+    while let Some(index) = rest.find(MACRO_CALL) {
+        let line = source.len() - rest.len() + index;
+        rest = rest[index + MACRO_CALL.len()..].trim_start();
+        if let Some(literal) = rest.strip_prefix('"')
+            && let Some(end) = literal.find('"')
+        {
+            let line = source[..line].lines().count() + 1;
+            keys.push((line, literal[..end].to_owned()));
+        }
+    }
+    keys
+}
+
+/// Find the source files in a directory
+fn source_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), anyhow::Error> {
+    for entry in fs::read_dir(dir).context("Could not read source directory")? {
+        let path = entry
+            .context("Could not read source directory entry")?
+            .path();
+        if path.is_dir() {
+            source_files(&path, files)?;
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Check the translations in a directory against the default language, and the source if given
+///
+/// The keys the build knows of are already checked when compiling, so the source is only needed
+/// to point at the lines that use unknown keys.
+pub(crate) fn check_translations(
+    translations_dir: &Path,
+    source_dir: Option<&Path>,
+) -> Result<Vec<TranslationIssue>, anyhow::Error> {
+    let mut issues = Vec::new();
+
+    // Load every translations file
+    let mut translations = BTreeMap::new();
+    let mut default_name = None;
+    for entry in fs::read_dir(translations_dir).context("Could not read translations directory")? {
+        let path = entry
+            .context("Could not read translations directory entry")?
+            .path();
+        if path.extension().is_none_or(|extension| extension != "yaml") {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let content = fs::read(&path).with_context(|| format!("Could not read {}", name))?;
+        let value = serde_yaml::from_slice::<Value>(&content)
+            .with_context(|| format!("Could not parse {}", name))?;
+
+        // Check the language the file describes
+        let mut default_file = None;
+        if let Some(meta) = value.get(META_KEY) {
+            match Language::from_meta(name.as_str(), meta) {
+                Ok(language) if language.tag() == DEFAULT_LANGUAGE_TAG => {
+                    default_file = Some(name.to_owned())
+                }
+                Ok(_) => {}
+                Err(err) => issues.push(TranslationIssue::InvalidLanguage {
+                    file: name.to_owned(),
+                    error: err.to_string(),
+                }),
+            }
+        }
+
+        // Check the names Discord will be given
+        let mut command_names_found = Vec::new();
+        if let Some(commands) = value.get(COMMANDS_KEY) {
+            command_names(commands, COMMANDS_KEY, &mut command_names_found);
+        }
+        for (key, command_name) in command_names_found {
+            if !is_valid_name(&command_name) {
+                issues.push(TranslationIssue::InvalidCommandName {
+                    file: name.to_owned(),
+                    key,
+                    name: command_name,
+                });
+            }
+        }
+
+        let mut messages = BTreeMap::new();
+        flatten(&value, "", &mut messages);
+        translations.insert(name.to_owned(), (value.get(META_KEY).is_some(), messages));
+        default_name = default_name.or(default_file);
+    }
+
+    // Compare every file against the default language
+    let default_name = default_name
+        .with_context(|| format!("Missing translations for {}", DEFAULT_LANGUAGE_TAG))?;
+    let (_, default) = translations
+        .get(&default_name)
+        .with_context(|| format!("Missing translations for {}", default_name))?;
+    for (name, (complete, messages)) in &translations {
+        if *name == default_name {
+            continue;
+        }
+
+        // Only the files describing a language have to be complete
+        for (key, expected) in default {
+            match messages.get(key) {
+                None if *complete => issues.push(TranslationIssue::MissingKey {
+                    file: name.to_owned(),
+                    key: key.to_owned(),
+                }),
+                None => {}
+                Some(found) if found != expected => {
+                    issues.push(TranslationIssue::PlaceholderMismatch {
+                        file: name.to_owned(),
+                        key: key.to_owned(),
+                        expected: expected.to_owned(),
+                        found: found.to_owned(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for key in messages.keys() {
+            if !default.contains_key(key) {
+                issues.push(TranslationIssue::ExtraKey {
+                    file: name.to_owned(),
+                    key: key.to_owned(),
+                });
+            }
+        }
+    }
+
+    // Check that keys used by the code exist
+    let known = |key: &str| {
+        translations
+            .values()
+            .any(|(_, messages)| messages.contains_key(key))
+    };
+    let mut files = Vec::new();
+    if let Some(source_dir) = source_dir {
+        source_files(source_dir, &mut files)?;
+    }
+    files.sort();
+    for file in files {
+        let source = fs::read_to_string(&file)
+            .with_context(|| format!("Could not read {}", file.display()))?;
+        for (line, key) in source_keys(&source) {
+            if !known(&key) {
+                issues.push(TranslationIssue::UnknownKey {
+                    location: format!("{}:{}", file.display(), line),
+                    key,
+                });
+            }
+        }
+    }
+    for setting in SETTINGS {
+        let keys = [setting.label]
+            .into_iter()
+            .chain(
+                setting
+                    .choices()
+                    .into_iter()
+                    .filter_map(|choice| match choice.label {
+                        ChoiceLabel::Key(key) => Some(key),
+                        ChoiceLabel::Text(_) => None,
+                    }),
+            );
+        for key in keys {
+            if !known(key) {
+                issues.push(TranslationIssue::UnknownKey {
+                    location: format!("setting {}", setting.key),
+                    key: key.to_owned(),
+                });
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_names() {
+        let value = serde_yaml::from_str::<Value>(
+            "settings:\n  name: \"Ajustes\"\n  subcommands:\n    timezone:\n      name: \"zona_horaria\"\n      parameters:\n        server:\n          name: \"servidor\"\n",
+        )
+        .unwrap();
+        let mut names = Vec::new();
+        command_names(&value, COMMANDS_KEY, &mut names);
+        assert_eq!(
+            names,
+            vec![
+                ("commands.settings.name".to_owned(), "Ajustes".to_owned()),
+                (
+                    "commands.settings.subcommands.timezone.name".to_owned(),
+                    "zona_horaria".to_owned()
+                ),
+                (
+                    "commands.settings.subcommands.timezone.parameters.server.name".to_owned(),
+                    "servidor".to_owned()
+                ),
+            ]
+        );
+        assert!(!is_valid_name(&names[0].1));
+    }
+
+    #[test]
+    fn test_translations_complete() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let issues = check_translations(&root.join("lang"), Some(&root.join("src"))).unwrap();
+        assert!(
+            issues.is_empty(),
+            "Translation issues found:\n{}",
+            issues
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+}
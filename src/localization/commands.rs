@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use serde_yaml::Value;
+use tracing::{Level, event};
+
+use super::{Language, loader::get_translations};
+
+/// The key of the section of a translations file with the names and descriptions of commands
+pub(super) const COMMANDS_KEY: &str = "commands";
+
+/// The most characters Discord allows in a command or parameter name
+const MAX_NAME_LENGTH: usize = 32;
+
+/// Check if Discord accepts a command or parameter name, which is 1 to 32 lowercase letters,
+/// numbers, dashes and underscores
+pub(super) fn is_valid_name(name: &str) -> bool {
+    (1..=MAX_NAME_LENGTH).contains(&name.chars().count())
+        && name.chars().all(|char| {
+            (char.is_alphanumeric() && !char.is_uppercase()) || char == '-' || char == '_'
+        })
+}
+
+/// A localized name and description
+struct Localization<'a> {
+    name: Option<&'a str>,
+    description: Option<&'a str>,
+}
+
+impl<'a> Localization<'a> {
+    fn from_value(value: Option<&'a Value>) -> Self {
+        let field = |name| {
+            value
+                .and_then(|value| value.get(name))
+                .and_then(Value::as_str)
+        };
+        Self {
+            name: field("name"),
+            description: field("description"),
+        }
+    }
+
+    /// Add the localization for a locale to the maps of a command or parameter
+    fn apply(
+        &self,
+        locale: &str,
+        name_localizations: &mut HashMap<String, String>,
+        description_localizations: &mut HashMap<String, String>,
+    ) {
+        // Discord refuses to register every command if a name is invalid
+        match self.name {
+            Some(name) if is_valid_name(name) => {
+                name_localizations.insert(locale.to_owned(), name.to_owned());
+            }
+            Some(name) => event!(
+                Level::WARN,
+                "Skipping invalid command name `{}` for {}",
+                name,
+                locale
+            ),
+            None => {}
+        }
+        if let Some(description) = self.description {
+            description_localizations.insert(locale.to_owned(), description.to_owned());
+        }
+    }
+}
+
+/// Localize a command and its subcommands and parameters from the translations of a language
+fn localize_command<U, E>(
+    command: &mut poise::Command<U, E>,
+    translations: Option<&Value>,
+    language: &Language,
+    is_default: bool,
+) {
+    let command_localization = Localization::from_value(translations);
+    let locale = language.discord_locale();
+
+    // Localize the command
+    if let Some(locale) = locale {
+        command_localization.apply(
+            locale,
+            &mut command.name_localizations,
+            &mut command.description_localizations,
+        );
+    }
+    if is_default && let Some(description) = command_localization.description {
+        command.description = Some(description.to_owned());
+    }
+
+    // Localize the parameters
+    for parameter in &mut command.parameters {
+        let parameter_localization = Localization::from_value(
+            translations
+                .and_then(|translations| translations.get("parameters"))
+                .and_then(|parameters| parameters.get(&parameter.name)),
+        );
+        if let Some(locale) = locale {
+            parameter_localization.apply(
+                locale,
+                &mut parameter.name_localizations,
+                &mut parameter.description_localizations,
+            );
+        }
+        if is_default && let Some(description) = parameter_localization.description {
+            parameter.description = Some(description.to_owned());
+        }
+    }
+
+    // Localize the subcommands
+    for subcommand in &mut command.subcommands {
+        let translations = translations
+            .and_then(|translations| translations.get("subcommands"))
+            .and_then(|subcommands| subcommands.get(&subcommand.name));
+        localize_command(subcommand, translations, language, is_default);
+    }
+}
+
+/// Apply the names and descriptions in the translations to a list of commands
+pub(crate) fn localize_commands<U, E>(commands: &mut [poise::Command<U, E>]) {
+    let translations = get_translations();
+    let default = Language::default();
+
+    for language in &translations.languages {
+        // Get the command translations of the language
+        let Some(command_translations) = translations
+            .files
+            .get(language.file())
+            .and_then(|translations| translations.get(COMMANDS_KEY))
+        else {
+            event!(
+                Level::WARN,
+                "No command translations for {}",
+                language.tag()
+            );
+            continue;
+        };
+
+        if language.discord_locale().is_none() {
+            event!(
+                Level::WARN,
+                "{} has no Discord locale, so commands will not be localized in it",
+                language.tag()
+            );
+        }
+
+        for command in commands.iter_mut() {
+            localize_command(
+                command,
+                command_translations.get(&command.name),
+                language,
+                *language == default,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::get_all_commands;
+
+    use super::*;
+
+    #[test]
+    fn test_invalid_names_skipped() {
+        for name in ["ajustes", "zona_horaria", "delete-guild", "año", "日本語"] {
+            assert!(is_valid_name(name), "{}", name);
+        }
+        for name in [
+            "Ajustes",
+            "zona horaria",
+            "",
+            "a".repeat(33).as_str(),
+            "ajustes!",
+        ] {
+            assert!(!is_valid_name(name), "{}", name);
+        }
+
+        let (mut names, mut descriptions) = (HashMap::new(), HashMap::new());
+        Localization {
+            name: Some("Ajustes"),
+            description: Some("Cambia tus ajustes"),
+        }
+        .apply("es-419", &mut names, &mut descriptions);
+        assert!(names.is_empty());
+        assert_eq!(descriptions["es-419"], "Cambia tus ajustes");
+    }
+
+    #[test]
+    fn test_localize_commands() {
+        let commands = get_all_commands();
+        let purge = commands
+            .iter()
+            .find(|command| command.name == "purge")
+            .unwrap();
+        assert_eq!(purge.name_localizations["es-419"], "purgar");
+
+        let from = &purge.subcommands[0];
+        assert_eq!(
+            from.description.as_deref(),
+            Some("Purge messages from a user")
+        );
+        assert_eq!(from.parameters[0].name_localizations["es-419"], "usuario");
+    }
+}
@@ -0,0 +1,154 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+use strum::{EnumIter, IntoEnumIterator as _};
+
+use super::Language;
+
+/// A format to display dates in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, EnumIter)]
+pub(crate) enum DateFormat {
+    #[default]
+    Auto,
+    Iso,
+    MonthFirst,
+    DayFirst,
+}
+
+impl DateFormat {
+    /// Get the code used to store the date format in settings
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Iso => "iso",
+            Self::MonthFirst => "mdy",
+            Self::DayFirst => "dmy",
+        }
+    }
+
+    /// Get a date format from the code used to store it in settings
+    pub(crate) fn from_code(code: &str) -> Option<Self> {
+        Self::iter().find(|format| format.code() == code)
+    }
+
+    /// Get the strftime pattern for the date format in a language
+    fn pattern<'a>(&self, language: &'a Language) -> &'a str {
+        match self {
+            Self::Auto => language.date_pattern(),
+            Self::Iso => "%Y-%m-%d %H:%M",
+            Self::MonthFirst => "%m/%d/%Y %I:%M %p",
+            Self::DayFirst => "%d/%m/%Y %H:%M",
+        }
+    }
+}
+
+/// Format a date and time in a timezone
+pub(crate) fn format_datetime(
+    datetime: DateTime<Utc>,
+    timezone: Tz,
+    format: DateFormat,
+    language: &Language,
+) -> String {
+    datetime
+        .with_timezone(&timezone)
+        .format(format.pattern(language))
+        .to_string()
+}
+
+/// Format an integer with the digit grouping of a language
+pub(crate) fn format_integer(integer: i64, language: &Language) -> String {
+    let digits = integer.unsigned_abs().to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if integer < 0 {
+        formatted.push('-');
+    }
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push_str(language.group_separator());
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// A unit of time used in durations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub(crate) enum TimeUnit {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl TimeUnit {
+    /// Get the name of the unit used in translations
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Year => "year",
+            Self::Month => "month",
+            Self::Day => "day",
+            Self::Hour => "hour",
+            Self::Minute => "minute",
+            Self::Second => "second",
+        }
+    }
+
+    /// Get the length of the unit in seconds
+    fn seconds(&self) -> i64 {
+        match self {
+            Self::Year => 365 * 24 * 60 * 60,
+            Self::Month => 30 * 24 * 60 * 60,
+            Self::Day => 24 * 60 * 60,
+            Self::Hour => 60 * 60,
+            Self::Minute => 60,
+            Self::Second => 1,
+        }
+    }
+}
+
+/// Split a duration into at most a number of units, from largest to smallest
+pub(crate) fn split_duration(duration: TimeDelta, max_units: usize) -> Vec<(TimeUnit, i64)> {
+    let mut remaining = duration.num_seconds().abs();
+    TimeUnit::iter()
+        .filter_map(|unit| {
+            let count = remaining / unit.seconds();
+            remaining %= unit.seconds();
+            (count > 0).then_some((unit, count))
+        })
+        .take(max_units)
+        .collect()
+}
+
+/// A style of Discord timestamp, which is shown in the locale and timezone of the reader
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TimestampStyle {
+    ShortTime,
+    LongTime,
+    ShortDate,
+    LongDate,
+    ShortDateTime,
+    LongDateTime,
+    Relative,
+}
+
+impl TimestampStyle {
+    /// Get the flag Discord uses for the style
+    fn flag(&self) -> char {
+        match self {
+            Self::ShortTime => 't',
+            Self::LongTime => 'T',
+            Self::ShortDate => 'd',
+            Self::LongDate => 'D',
+            Self::ShortDateTime => 'f',
+            Self::LongDateTime => 'F',
+            Self::Relative => 'R',
+        }
+    }
+}
+
+/// Format a date and time as Discord timestamp markup
+pub(crate) fn discord_timestamp(datetime: DateTime<Utc>, style: TimestampStyle) -> String {
+    format!("<t:{}:{}>", datetime.timestamp(), style.flag())
+}
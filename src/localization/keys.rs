@@ -0,0 +1,63 @@
+/// The keys of the default translations and the names of the arguments their messages use
+const KEYS: &[(&str, &[&str])] = include!(concat!(env!("OUT_DIR"), "/translation_keys.rs"));
+
+/// Check if two strings are equal in a constant context
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Check if a list of strings contains a string in a constant context
+const fn contains(list: &[&str], item: &str) -> bool {
+    let mut i = 0;
+    while i < list.len() {
+        if str_eq(list[i], item) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Get the names of the arguments a key uses in a constant context
+const fn get_arguments(key: &str) -> Option<&'static [&'static str]> {
+    let mut i = 0;
+    while i < KEYS.len() {
+        if str_eq(KEYS[i].0, key) {
+            return Some(KEYS[i].1);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Check that a key exists and is given exactly the arguments it uses, failing the build if not
+pub(crate) const fn check_arguments(key: &str, args: &[&str]) {
+    let Some(expected) = get_arguments(key) else {
+        panic!("Unknown translation key");
+    };
+    let mut i = 0;
+    while i < args.len() {
+        if !contains(expected, args[i]) {
+            panic!("Argument is not used by the translation");
+        }
+        i += 1;
+    }
+    let mut i = 0;
+    while i < expected.len() {
+        if !contains(args, expected[i]) {
+            panic!("Missing argument for the translation");
+        }
+        i += 1;
+    }
+}
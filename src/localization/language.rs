@@ -1,8 +1,17 @@
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 
-use strum::EnumIter;
+use serde_yaml::Value;
 
-use crate::db;
+use super::languages;
+
+/// The tag of the language used when no other language is available
+pub(crate) const DEFAULT_LANGUAGE_TAG: &str = "en-US";
+
+/// The separator between groups of digits used when a language does not give one
+const DEFAULT_GROUP_SEPARATOR: &str = ",";
+
+/// The strftime pattern for dates used when a language does not give one
+const DEFAULT_DATE_PATTERN: &str = "%Y-%m-%d %H:%M";
 
 #[derive(Debug, Clone)]
 pub(crate) struct LanguageParseError {
@@ -23,67 +32,254 @@ impl std::fmt::Display for LanguageParseError {
 
 impl std::error::Error for LanguageParseError {}
 
-#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, EnumIter)]
-pub(crate) enum Language {
-    #[default]
-    English,
-    Spanish,
+/// An error in the metadata of a translations file
+#[derive(Debug, Clone)]
+pub(crate) enum LanguageMetaError {
+    MissingField(&'static str),
+    UnknownPluralRule(String),
 }
 
-impl Language {
-    pub(crate) fn as_str(&self) -> &'static str {
+impl std::fmt::Display for LanguageMetaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::English => "en_US",
-            Self::Spanish => "es_419",
+            Self::MissingField(field) => write!(f, "Missing language metadata field: {}", field),
+            Self::UnknownPluralRule(rule) => write!(f, "Unknown plural rule: {}", rule),
         }
     }
 }
 
-impl FromStr for Language {
-    type Err = LanguageParseError;
+impl std::error::Error for LanguageMetaError {}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "en-US" => Ok(Self::English),
-            "es-419" => Ok(Self::Spanish),
-            other => Err(LanguageParseError::new(other.to_owned())),
+/// A plural category of a number
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Zero => "zero",
+            Self::One => "one",
+            Self::Two => "two",
+            Self::Few => "few",
+            Self::Many => "many",
+            Self::Other => "other",
         }
     }
 }
 
-impl From<db::guild_settings::Language> for Language {
-    fn from(value: db::guild_settings::Language) -> Self {
-        match value {
-            db::guild_settings::Language::English => Self::English,
-            db::guild_settings::Language::Spanish => Self::Spanish,
+/// A rule for finding the plural category of a number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum PluralRule {
+    /// One for 1 and other for everything else, as in English and Spanish
+    OneOther,
+    /// One for 0 and 1 and other for everything else, as in French
+    ZeroOneOther,
+    /// One, few and many depending on the last digits, as in Russian and Ukrainian
+    EastSlavic,
+    /// Other for every number, as in Japanese and Chinese
+    Other,
+}
+
+impl PluralRule {
+    /// Get the name of the rule used in translations files
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::OneOther => "one_other",
+            Self::ZeroOneOther => "zero_one_other",
+            Self::EastSlavic => "east_slavic",
+            Self::Other => "other",
         }
     }
-}
 
-impl From<db::user_settings::Language> for Language {
-    fn from(value: db::user_settings::Language) -> Self {
-        match value {
-            db::user_settings::Language::English => Self::English,
-            db::user_settings::Language::Spanish => Self::Spanish,
+    /// Get the plural category of a number
+    pub(crate) fn category(&self, number: i64) -> PluralCategory {
+        let number = number.unsigned_abs();
+        match self {
+            Self::OneOther if number == 1 => PluralCategory::One,
+            Self::ZeroOneOther if number <= 1 => PluralCategory::One,
+            Self::EastSlavic => match (number % 10, number % 100) {
+                (1, last) if last != 11 => PluralCategory::One,
+                (2..=4, last) if !(12..=14).contains(&last) => PluralCategory::Few,
+                _ => PluralCategory::Many,
+            },
+            _ => PluralCategory::Other,
         }
     }
 }
 
-impl From<Language> for db::guild_settings::Language {
-    fn from(value: Language) -> Self {
-        match value {
-            Language::English => Self::English,
-            Language::Spanish => Self::Spanish,
-        }
+impl FromStr for PluralRule {
+    type Err = LanguageMetaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::OneOther,
+            Self::ZeroOneOther,
+            Self::EastSlavic,
+            Self::Other,
+        ]
+        .into_iter()
+        .find(|rule| rule.as_str() == s)
+        .ok_or(LanguageMetaError::UnknownPluralRule(s.to_owned()))
     }
 }
 
-impl From<Language> for db::user_settings::Language {
-    fn from(value: Language) -> Self {
-        match value {
-            Language::English => Self::English,
-            Language::Spanish => Self::Spanish,
+/// A language that translations are available in
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Language {
+    /// The BCP-47 tag of the language, which is stored in settings
+    tag: Arc<str>,
+    /// The name of the language in itself
+    name: Arc<str>,
+    /// The Discord locale matching the language
+    discord_locale: Option<Arc<str>>,
+    /// The rule for the plural categories of numbers
+    plural_rule: PluralRule,
+    /// The separator between groups of three digits in numbers
+    group_separator: Arc<str>,
+    /// The strftime pattern for dates
+    date_pattern: Arc<str>,
+    /// The name of the translations file of the language
+    file: Arc<str>,
+}
+
+impl Language {
+    /// Read a language from the metadata of a translations file
+    pub(crate) fn from_meta(
+        file: impl Into<Arc<str>>,
+        meta: &Value,
+    ) -> Result<Self, LanguageMetaError> {
+        let field = |name: &'static str| {
+            meta.get(name)
+                .and_then(Value::as_str)
+                .ok_or(LanguageMetaError::MissingField(name))
+        };
+        Ok(Self {
+            tag: field("tag")?.into(),
+            name: field("name")?.into(),
+            discord_locale: field("discord_locale").ok().map(Into::into),
+            plural_rule: field("plural")?.parse()?,
+            group_separator: field("group_separator")
+                .unwrap_or(DEFAULT_GROUP_SEPARATOR)
+                .into(),
+            date_pattern: field("date_pattern").unwrap_or(DEFAULT_DATE_PATTERN).into(),
+            file: file.into(),
+        })
+    }
+
+    /// Get the BCP-47 tag of the language
+    pub(crate) fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Get the name of the language in itself
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the Discord locale matching the language
+    pub(crate) fn discord_locale(&self) -> Option<&str> {
+        self.discord_locale.as_deref()
+    }
+
+    /// Get the separator between groups of three digits in numbers
+    pub(crate) fn group_separator(&self) -> &str {
+        &self.group_separator
+    }
+
+    /// Get the strftime pattern for dates
+    pub(crate) fn date_pattern(&self) -> &str {
+        &self.date_pattern
+    }
+
+    /// Get the name of the translations file of the language
+    pub(crate) fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Get the names of the translations to use for the language, from most to least preferred
+    pub(crate) fn fallback_chain(&self) -> Vec<String> {
+        let mut chain = vec![self.file().to_owned()];
+
+        // Fall back to the base language without a region
+        if let Some((base, _)) = self.tag().split_once('-') {
+            chain.push(base.to_owned());
+        }
+
+        // Fall back to the default language
+        let default = Self::default();
+        if *self != default {
+            chain.push(default.file().to_owned());
         }
+
+        chain
+    }
+
+    /// Get a language from a tag or Discord locale, falling back to the base language for other regions
+    pub(crate) fn find(tag: &str) -> Option<Self> {
+        let tag = tag.replace('_', "-");
+        let languages = languages();
+
+        // Look for the exact language
+        let exact = languages.iter().find(|language| {
+            language.tag().eq_ignore_ascii_case(&tag)
+                || language
+                    .discord_locale()
+                    .is_some_and(|locale| locale.eq_ignore_ascii_case(&tag))
+        });
+        if let Some(language) = exact {
+            return Some(language.to_owned());
+        }
+
+        // Look for a language with the same base
+        let base = |tag: &str| {
+            tag.split('-')
+                .next()
+                .unwrap_or_default()
+                .to_ascii_lowercase()
+        };
+        languages
+            .iter()
+            .find(|language| base(language.tag()) == base(&tag))
+            .cloned()
+    }
+
+    /// Get the plural category of a number in the language
+    pub(crate) fn plural_category(&self, number: i64) -> PluralCategory {
+        self.plural_rule.category(number)
+    }
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        languages()
+            .iter()
+            .find(|language| language.tag() == DEFAULT_LANGUAGE_TAG)
+            .cloned()
+            .unwrap_or_else(|| Self {
+                tag: DEFAULT_LANGUAGE_TAG.into(),
+                name: "English".into(),
+                discord_locale: Some(DEFAULT_LANGUAGE_TAG.into()),
+                plural_rule: PluralRule::OneOther,
+                group_separator: DEFAULT_GROUP_SEPARATOR.into(),
+                date_pattern: DEFAULT_DATE_PATTERN.into(),
+                file: DEFAULT_LANGUAGE_TAG.replace('-', "_").into(),
+            })
+    }
+}
+
+impl FromStr for Language {
+    type Err = LanguageParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::find(s).ok_or(LanguageParseError::new(s.to_owned()))
     }
 }
 
@@ -95,9 +291,6 @@ impl AsRef<Language> for Language {
 
 impl std::fmt::Display for Language {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::English => write!(f, "English"),
-            Self::Spanish => write!(f, "Spanish"),
-        }
+        write!(f, "{}", self.name)
     }
 }
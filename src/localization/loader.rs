@@ -0,0 +1,259 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, RwLock},
+    time::Duration,
+};
+
+use notify_debouncer_mini::{
+    DebounceEventResult, Debouncer, new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+};
+use serde_yaml::Value;
+use tracing::{Level, event};
+
+use super::{Language, USED_FALLBACKS, template::META_KEY};
+use crate::config::config;
+
+/// How long to wait for changes to the translations to settle before reloading them
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Translations keyed by the name of the file they were loaded from
+pub(super) type TranslationsMap = HashMap<String, Value>;
+
+/// The translations and the languages discovered from them
+#[derive(Debug, Default)]
+pub(super) struct Translations {
+    pub languages: Vec<Language>,
+    pub files: TranslationsMap,
+}
+
+static TRANSLATIONS: LazyLock<RwLock<Arc<Translations>>> = LazyLock::new(|| {
+    let (translations, errors) = load_translations(&translations_dir());
+    for error in errors {
+        event!(Level::ERROR, "Failed to load translations: {}", error);
+    }
+    event!(
+        Level::INFO,
+        "Loaded translations for {} languages",
+        translations.languages.len()
+    );
+    RwLock::new(Arc::new(translations))
+});
+
+/// An error in a translations file
+#[derive(Debug, Clone)]
+pub(crate) struct TranslationsFileError {
+    file: PathBuf,
+    line: Option<usize>,
+    column: Option<usize>,
+    message: String,
+}
+
+impl TranslationsFileError {
+    fn new(file: impl Into<PathBuf>, message: impl ToString) -> Self {
+        Self {
+            file: file.into(),
+            line: None,
+            column: None,
+            message: message.to_string(),
+        }
+    }
+
+    fn from_yaml(file: impl Into<PathBuf>, err: serde_yaml::Error) -> Self {
+        let location = err.location();
+        Self {
+            line: location.as_ref().map(|location| location.line()),
+            column: location.as_ref().map(|location| location.column()),
+            ..Self::new(file, err)
+        }
+    }
+}
+
+impl std::fmt::Display for TranslationsFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        if let Some(column) = self.column {
+            write!(f, ":{}", column)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for TranslationsFileError {}
+
+/// An error from reloading the translations
+#[derive(Debug, Clone)]
+pub(crate) struct TranslationsReloadError {
+    pub errors: Vec<TranslationsFileError>,
+}
+
+impl std::fmt::Display for TranslationsReloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Failed to load {} translations files: {}",
+            self.errors.len(),
+            self.errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; ")
+        )
+    }
+}
+
+impl std::error::Error for TranslationsReloadError {}
+
+/// Get the directory translations are loaded from
+pub(crate) fn translations_dir() -> PathBuf {
+    config().translations_dir.clone()
+}
+
+/// Load every translations file in a directory, discovering the languages described in them
+fn load_translations(translations_dir: &Path) -> (Translations, Vec<TranslationsFileError>) {
+    let mut translations = Translations::default();
+    let mut errors = Vec::new();
+
+    // Get the translations files
+    let entries = match fs::read_dir(translations_dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            errors.push(TranslationsFileError::new(translations_dir, err));
+            return (translations, errors);
+        }
+    };
+
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        // Only load yaml files
+        if path.extension().is_none_or(|extension| extension != "yaml") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        // Parse the file
+        let content = match fs::read(&path) {
+            Ok(content) => content,
+            Err(err) => {
+                errors.push(TranslationsFileError::new(&path, err));
+                continue;
+            }
+        };
+        let value = match serde_yaml::from_slice::<Value>(&content) {
+            Ok(value) => value,
+            Err(err) => {
+                errors.push(TranslationsFileError::from_yaml(&path, err));
+                continue;
+            }
+        };
+
+        // Discover the language the file describes
+        if let Some(meta) = value.get(META_KEY) {
+            match Language::from_meta(name, meta) {
+                Ok(language) => translations.languages.push(language),
+                Err(err) => errors.push(TranslationsFileError::new(&path, err)),
+            }
+        }
+
+        translations.files.insert(name.to_owned(), value);
+    }
+
+    // Keep the languages in a stable order
+    translations.languages.sort_by(|a, b| a.tag().cmp(b.tag()));
+
+    (translations, errors)
+}
+
+/// Get the current translations
+#[inline]
+pub(super) fn get_translations() -> Arc<Translations> {
+    TRANSLATIONS
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Get the languages that translations are available in
+pub(crate) fn languages() -> Vec<Language> {
+    get_translations().languages.clone()
+}
+
+/// Reload the translations, keeping the current ones if any file has an error
+pub(crate) fn reload_translations() -> Result<usize, TranslationsReloadError> {
+    let (translations, errors) = load_translations(&translations_dir());
+    if !errors.is_empty() {
+        return Err(TranslationsReloadError { errors });
+    }
+
+    // Swap in the new translations
+    let language_count = translations.languages.len();
+    *TRANSLATIONS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(translations);
+
+    // Warn about fallbacks again, since the missing translations may have changed
+    USED_FALLBACKS.clear();
+
+    Ok(language_count)
+}
+
+/// Watch the translations directory and reload the translations when it changes
+pub(crate) fn watch_translations() -> Result<Debouncer<RecommendedWatcher>, anyhow::Error> {
+    let mut debouncer = new_debouncer(WATCH_DEBOUNCE, |result: DebounceEventResult| {
+        match result {
+            Ok(events) => {
+                // Only reload for translations files
+                if !events.iter().any(|event| {
+                    event
+                        .path
+                        .extension()
+                        .is_some_and(|extension| extension == "yaml")
+                }) {
+                    return;
+                }
+
+                match reload_translations() {
+                    Ok(language_count) => event!(
+                        Level::INFO,
+                        "Reloaded translations for {} languages",
+                        language_count
+                    ),
+                    Err(err) => event!(Level::ERROR, "{}", err),
+                }
+            }
+            Err(err) => event!(Level::ERROR, "Error watching translations: {}", err),
+        }
+    })?;
+    debouncer
+        .watcher()
+        .watch(&translations_dir(), RecursiveMode::NonRecursive)?;
+    Ok(debouncer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_load_translations_reports_location() {
+        let dir = env::temp_dir().join(format!("awb-translations-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("bad.yaml"), "command:\n  hello: \"unterminated\n").unwrap();
+
+        let (translations, errors) = load_translations(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(translations.files.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].file.ends_with("bad.yaml"));
+        assert!(errors[0].line.is_some());
+    }
+}
@@ -0,0 +1,260 @@
+use serde_yaml::Value;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+
+use super::{
+    Language, LocalizationError, TranslationKey, find_translation,
+    format::{
+        DateFormat, TimeUnit, TimestampStyle, discord_timestamp, format_datetime, format_integer,
+        split_duration,
+    },
+    template::SELECT_KEY,
+};
+
+/// The variant of a message used when no other variant matches
+const OTHER_VARIANT: &str = "other";
+
+/// The key of the translation shown for a relative time in the past
+const RELATIVE_PAST_KEY: &str = "format.relative.past";
+
+/// The key of the translation shown for a relative time in the future
+const RELATIVE_FUTURE_KEY: &str = "format.relative.future";
+
+/// The key of the translation shown for a relative time that is now
+const RELATIVE_NOW_KEY: &str = "format.relative.now";
+
+/// The key of the translation joining the units of a duration
+const DURATION_JOIN_KEY: &str = "format.duration.join";
+
+/// The most units shown in a duration
+const DURATION_MAX_UNITS: usize = 2;
+
+/// An argument to a localized message
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Argument {
+    /// A number, shown with the digit grouping of the language
+    Integer(i64),
+    /// Text shown as it is
+    Text(String),
+    /// A date and time shown in a timezone and format
+    Date {
+        datetime: DateTime<Utc>,
+        timezone: Tz,
+        format: DateFormat,
+    },
+    /// A date and time shown relative to now, like 3 days ago
+    Relative(DateTime<Utc>),
+    /// A length of time, like 2 hours, 5 minutes
+    Duration(TimeDelta),
+    /// A date and time shown by Discord in the locale and timezone of the reader
+    Timestamp(DateTime<Utc>, TimestampStyle),
+}
+
+macro_rules! impl_from_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for Argument {
+                fn from(value: $integer) -> Self {
+                    Self::Integer(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<u64> for Argument {
+    fn from(value: u64) -> Self {
+        Self::Integer(i64::try_from(value).unwrap_or(i64::MAX))
+    }
+}
+
+impl From<usize> for Argument {
+    fn from(value: usize) -> Self {
+        Self::Integer(i64::try_from(value).unwrap_or(i64::MAX))
+    }
+}
+
+impl From<String> for Argument {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&String> for Argument {
+    fn from(value: &String) -> Self {
+        Self::Text(value.to_owned())
+    }
+}
+
+impl From<&str> for Argument {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_owned())
+    }
+}
+
+/// Get an argument by its name
+fn get_argument<'a>(
+    args: &'a [(&str, Argument)],
+    name: &str,
+) -> Result<&'a Argument, LocalizationError> {
+    args.iter()
+        .find(|(arg_name, _)| *arg_name == name)
+        .map(|(_, arg)| arg)
+        .ok_or(LocalizationError::MissingArgument(name.to_owned()))
+}
+
+/// Renders translations into messages in a language
+pub(super) struct Renderer<'a> {
+    language: &'a Language,
+    chain: &'a [(String, &'a Value)],
+}
+
+impl<'a> Renderer<'a> {
+    /// Create a renderer for a language and the translations it can use
+    pub(super) fn new(language: &'a Language, chain: &'a [(String, &'a Value)]) -> Self {
+        Self { language, chain }
+    }
+
+    /// Find a translation used for formatting arguments
+    fn lookup(&self, key: &str) -> Result<(TranslationKey, &'a Value), LocalizationError> {
+        let translation_key = key.parse::<TranslationKey>()?;
+        let translation = self
+            .chain
+            .iter()
+            .find_map(|(_, translations)| find_translation(translations, &translation_key))
+            .ok_or(LocalizationError::UnknownKey(translation_key.clone()))?;
+        Ok((translation_key, translation))
+    }
+
+    /// Render the translation for a key used for formatting arguments
+    fn render_key(
+        &self,
+        key: &str,
+        args: &[(&str, Argument)],
+    ) -> Result<String, LocalizationError> {
+        let (translation_key, translation) = self.lookup(key)?;
+        self.render(translation, &translation_key, args)
+    }
+
+    /// Render a translation into a message
+    pub(super) fn render(
+        &self,
+        translation: &Value,
+        translation_key: &TranslationKey,
+        args: &[(&str, Argument)],
+    ) -> Result<String, LocalizationError> {
+        match translation {
+            Value::String(template) => self.substitute(template, args),
+            Value::Mapping(mapping) => {
+                // Get the argument the variant is selected by
+                let selector = mapping
+                    .get(SELECT_KEY)
+                    .and_then(Value::as_str)
+                    .ok_or(LocalizationError::UnknownKey(translation_key.clone()))?;
+                let arg = get_argument(args, selector)?;
+
+                // Find the matching variant
+                let variant = match arg {
+                    Argument::Integer(integer) => mapping
+                        .get(format!("={integer}"))
+                        .or_else(|| mapping.get(self.language.plural_category(*integer).as_str())),
+                    Argument::Text(text) => mapping.get(text),
+                    _ => None,
+                }
+                .or_else(|| mapping.get(OTHER_VARIANT))
+                .ok_or(LocalizationError::UnknownKey(translation_key.clone()))?;
+
+                self.render(variant, translation_key, args)
+            }
+            _ => Err(LocalizationError::UnknownKey(translation_key.clone())),
+        }
+    }
+
+    /// Format an argument for a message
+    fn format_argument(&self, arg: &Argument) -> Result<String, LocalizationError> {
+        match arg {
+            Argument::Integer(integer) => Ok(format_integer(*integer, self.language)),
+            Argument::Text(text) => Ok(text.replace("\\n", "\n").replace("\\t", "\t")),
+            Argument::Date {
+                datetime,
+                timezone,
+                format,
+            } => Ok(format_datetime(
+                *datetime,
+                *timezone,
+                *format,
+                self.language,
+            )),
+            Argument::Relative(datetime) => {
+                let offset = *datetime - Utc::now();
+                let Some(&(unit, count)) = split_duration(offset, 1).first() else {
+                    return self.render_key(RELATIVE_NOW_KEY, &[]);
+                };
+                let time = self.format_unit(unit, count)?;
+                let key = if offset < TimeDelta::zero() {
+                    RELATIVE_PAST_KEY
+                } else {
+                    RELATIVE_FUTURE_KEY
+                };
+                self.render_key(key, &[("time", time.into())])
+            }
+            Argument::Duration(duration) => {
+                let parts = split_duration(*duration, DURATION_MAX_UNITS)
+                    .into_iter()
+                    .map(|(unit, count)| self.format_unit(unit, count))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match parts.as_slice() {
+                    [] => self.format_unit(TimeUnit::Second, 0),
+                    [single] => Ok(single.to_owned()),
+                    [first, second, ..] => self.render_key(
+                        DURATION_JOIN_KEY,
+                        &[("first", first.into()), ("second", second.into())],
+                    ),
+                }
+            }
+            Argument::Timestamp(datetime, style) => Ok(discord_timestamp(*datetime, *style)),
+        }
+    }
+
+    /// Format a count of a unit of time
+    fn format_unit(&self, unit: TimeUnit, count: i64) -> Result<String, LocalizationError> {
+        self.render_key(
+            &format!("format.duration.units.{}", unit.as_str()),
+            &[("count", count.into())],
+        )
+    }
+
+    /// Replace the named placeholders in a template with their arguments
+    fn substitute(
+        &self,
+        template: &str,
+        args: &[(&str, Argument)],
+    ) -> Result<String, LocalizationError> {
+        let mut message = String::with_capacity(template.len());
+        let mut chars = template.chars().peekable();
+        while let Some(char) = chars.next() {
+            match char {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    message.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    message.push('}');
+                }
+                '{' => {
+                    let name = chars
+                        .by_ref()
+                        .take_while(|char| *char != '}')
+                        .collect::<String>();
+                    message.push_str(&self.format_argument(get_argument(args, name.trim())?)?);
+                }
+                char => message.push(char),
+            }
+        }
+        Ok(message)
+    }
+}
@@ -1,52 +1,38 @@
-use std::{collections::HashMap, env, path::PathBuf};
+use std::sync::LazyLock;
 
 use anyhow::Context as _;
+use dashmap::DashSet;
 use serde_yaml::Value;
-use strum::IntoEnumIterator;
-use tokio::{fs, sync::OnceCell};
+use tracing::{Level, event};
 
+pub(crate) use commands::localize_commands;
+pub(crate) use format::{DateFormat, TimestampStyle};
 pub(crate) use language::Language;
+pub(crate) use loader::{languages, reload_translations, translations_dir, watch_translations};
+pub(crate) use message::Argument;
 pub(crate) use translation_key::TranslationKey;
 
-use crate::localization::translation_key::TranslationKeyParseError;
+use crate::localization::{loader::get_translations, translation_key::TranslationKeyParseError};
 
+pub(crate) mod check;
+mod commands;
+mod format;
+pub(crate) mod keys;
 mod language;
+mod loader;
+mod message;
+mod template;
 mod translation_key;
 
-type TranslationsMap = HashMap<Language, Value>;
-
-static TRANSLATIONS: OnceCell<TranslationsMap> = OnceCell::const_new();
-
-#[inline]
-async fn get_translations() -> &'static TranslationsMap {
-    TRANSLATIONS
-        .get_or_init(|| async {
-            // Get the translations directory
-            let translations_dir =
-                PathBuf::from(env::var_os("TRANSLATIONS_DIR").unwrap_or("./lang".into()));
-
-            // Create translations map
-            let mut translations = TranslationsMap::new();
-
-            // Load languages
-            for language in Language::iter() {
-                fs::read(translations_dir.join([language.as_str(), ".yaml"].concat()))
-                    .await
-                    .ok()
-                    .and_then(|content| serde_yaml::from_slice(content.as_slice()).ok())
-                    .and_then(|value| translations.insert(language, value));
-            }
-
-            translations
-        })
-        .await
-}
+/// The languages and keys a fallback translation has been used for
+static USED_FALLBACKS: LazyLock<DashSet<(Language, String)>> = LazyLock::new(DashSet::new);
 
 #[derive(Debug, Clone)]
 pub(crate) enum LocalizationError {
     BadKey(TranslationKeyParseError),
     UnknownKey(TranslationKey),
     UnsupportedLanguage(Language),
+    MissingArgument(String),
 }
 
 impl From<TranslationKeyParseError> for LocalizationError {
@@ -61,8 +47,9 @@ impl std::fmt::Display for LocalizationError {
             Self::BadKey(key) => write!(f, "Bad key: {}", key),
             Self::UnknownKey(key) => write!(f, "Unknown key: {}", key.as_string()),
             Self::UnsupportedLanguage(language) => {
-                write!(f, "Unsupported language: {}", language.as_str())
+                write!(f, "Unsupported language: {}", language.tag())
             }
+            Self::MissingArgument(name) => write!(f, "Missing argument: {}", name),
         }
     }
 }
@@ -73,6 +60,7 @@ impl std::error::Error for LocalizationError {
             Self::BadKey(key) => Some(key),
             Self::UnknownKey(_) => None,
             Self::UnsupportedLanguage(_) => None,
+            Self::MissingArgument(_) => None,
         }
     }
 }
@@ -80,54 +68,105 @@ impl std::error::Error for LocalizationError {
 pub(crate) async fn localize_message(
     translation_key: impl AsRef<str>,
     language: impl AsRef<Language>,
-    args: &[&(dyn ToString + Send + Sync)],
+    args: &[(&str, Argument)],
 ) -> Result<String, anyhow::Error> {
     // Convert arguments
     let translation_key = translation_key.as_ref().parse::<TranslationKey>()?;
     let language = language.as_ref();
 
-    // Get the correct translations set
-    let translations = get_translations()
-        .await
-        .get(language)
-        .ok_or(LocalizationError::UnsupportedLanguage(language.to_owned()))
-        .context("Could not get translations")?;
+    // Get the translations sets the language can use
+    let translations = get_translations();
+    let translations = &translations.files;
+    let chain = language
+        .fallback_chain()
+        .into_iter()
+        .filter_map(|name| {
+            translations
+                .get(&name)
+                .map(|translations| (name, translations))
+        })
+        .collect::<Vec<_>>();
+    if chain.is_empty() {
+        return Err(LocalizationError::UnsupportedLanguage(language.to_owned()))
+            .context("Could not get translations");
+    }
+
+    // Get the translation from the first set that has it
+    let (name, translation) = chain
+        .iter()
+        .find_map(|(name, translations)| {
+            find_translation(translations, &translation_key).map(|translation| (name, translation))
+        })
+        .ok_or(LocalizationError::UnknownKey(translation_key.clone()))
+        .context("Could not find the key in any translations")?;
+
+    // Warn the first time a fallback is used
+    if name != language.file()
+        && USED_FALLBACKS.insert((language.to_owned(), translation_key.as_string()))
+    {
+        event!(
+            Level::WARN,
+            "Translation {} is missing for {}, falling back to {}",
+            translation_key.as_string(),
+            language.tag(),
+            name
+        );
+    }
+
+    // Render the translation with the arguments
+    message::Renderer::new(language, &chain)
+        .render(translation, &translation_key, args)
+        .context("Could not render the translation")
+}
 
-    // Get the correct translation
+/// Find the translation for a key by descending the tree of translations
+fn find_translation<'a>(
+    translations: &'a Value,
+    translation_key: &TranslationKey,
+) -> Option<&'a Value> {
     let mut current = translations;
     for component in translation_key.as_slice() {
         if let Value::Mapping(mapping) = current {
-            current = mapping
-                .get(component)
-                .ok_or(LocalizationError::UnknownKey(translation_key.clone()))
-                .context("Unknown key encountered while descending the tree")?;
+            current = mapping.get(component)?;
         } else {
-            return Err(LocalizationError::UnknownKey(translation_key.clone()))
-                .context("Unexpected value type while descending the tree");
+            return None;
         }
     }
-
-    // Return it as a string
-    if let Value::String(string) = current {
-        Ok(args
-            .iter()
-            .map(|arg| arg.to_string().replace("\\n", "\n").replace("\\t", "\t"))
-            .fold(string.to_owned(), |string, arg| {
-                string.replacen("{}", &arg, 1)
-            }))
-    } else {
-        Err(LocalizationError::UnknownKey(translation_key.clone()))
-            .context("Unexpected value type when getting translation")
-    }
+    Some(current)
 }
 
 #[macro_export]
 macro_rules! localize_message {
+    ($key:literal, $lang:expr $(,)?) => {
+        $crate::localization::localize_message(
+            {
+                const _: () = $crate::localization::keys::check_arguments($key, &[]);
+                $key
+            },
+            $lang,
+            &[],
+        )
+    };
+    ($key:literal, $lang:expr, $($name:ident = $arg:expr),* $(,)?) => {
+        $crate::localization::localize_message(
+            {
+                const _: () =
+                    $crate::localization::keys::check_arguments($key, &[$(stringify!($name)),*]);
+                $key
+            },
+            $lang,
+            &[$((stringify!($name), $crate::localization::Argument::from($arg))),*],
+        )
+    };
     ($key:expr, $lang:expr $(,)?) => {
         $crate::localization::localize_message($key, $lang, &[])
     };
-    ($key:expr, $lang:expr, $($args:expr),* $(,)?) => {
-        $crate::localization::localize_message($key, $lang, &[$(&$args),*])
+    ($key:expr, $lang:expr, $($name:ident = $arg:expr),* $(,)?) => {
+        $crate::localization::localize_message(
+            $key,
+            $lang,
+            &[$((stringify!($name), $crate::localization::Argument::from($arg))),*],
+        )
     };
 }
 
@@ -138,10 +177,68 @@ mod tests {
     #[tokio::test]
     async fn test_localize_message() {
         assert_eq!(
-            localize_message!("command.hello.response", &Language::English)
-                .await
-                .unwrap(),
+            localize_message!(
+                "command.hello.response",
+                &"en-US".parse::<Language>().unwrap(),
+                user = "Nater"
+            )
+            .await
+            .unwrap(),
             "Hello Nater"
         );
     }
+
+    #[tokio::test]
+    async fn test_localize_plural_message() {
+        assert_eq!(
+            localize_message!(
+                "command.purge.from.response.post",
+                &"en-US".parse::<Language>().unwrap(),
+                count = 1,
+                user = "Nater"
+            )
+            .await
+            .unwrap(),
+            "Purged 1 message from Nater"
+        );
+        assert_eq!(
+            localize_message!(
+                "command.purge.from.response.post",
+                &"es-419".parse::<Language>().unwrap(),
+                count = 3,
+                user = "Nater"
+            )
+            .await
+            .unwrap(),
+            "Se han purgado 3 mensajes de Nater"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_localize_formatted_arguments() {
+        let started = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        assert_eq!(
+            localize_message!(
+                "command.whoareyou.response",
+                &"es-419".parse::<Language>().unwrap(),
+                version = "1.0",
+                uptime = Argument::Duration(chrono::TimeDelta::seconds(2 * 86_400 + 3_600 + 5)),
+                started = Argument::Timestamp(started, TimestampStyle::ShortDateTime)
+            )
+            .await
+            .unwrap(),
+            "¡Hola! Soy el Alpha Wolf Bot versión `1.0` y llevo funcionando 2 días, 1 hora (desde <t:1700000000:f>)"
+        );
+        assert_eq!(
+            localize_message!(
+                "command.purge.from.response.post",
+                &"en-US".parse::<Language>().unwrap(),
+                count = 1234,
+                user = "Nater"
+            )
+            .await
+            .unwrap(),
+            "Purged 1,234 messages from Nater"
+        );
+    }
 }
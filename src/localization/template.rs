@@ -0,0 +1,111 @@
+//! Parsing of translation templates, shared with the build script
+//!
+//! The build script includes this file as a module, so it can only use `std` and `serde_yaml`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_yaml::{Mapping, Value};
+
+/// The key of the section of a translations file describing its language
+pub(crate) const META_KEY: &str = "meta";
+
+/// The key of a mapping that selects between variants of a message
+pub(crate) const SELECT_KEY: &str = "$select";
+
+/// Check if a mapping selects between variants of a message
+pub(crate) fn is_select(mapping: &Mapping) -> bool {
+    mapping.contains_key(SELECT_KEY)
+}
+
+/// Get the names of the placeholders in a template
+pub(crate) fn placeholders(template: &str, names: &mut BTreeSet<String>) {
+    let mut chars = template.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '{' | '}' if chars.peek() == Some(&char) => {
+                chars.next();
+            }
+            '{' => {
+                let name = chars
+                    .by_ref()
+                    .take_while(|char| *char != '}')
+                    .collect::<String>();
+                names.insert(name.trim().to_owned());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Get the names of the arguments used by a message, including the ones selecting its variant
+pub(crate) fn arguments(value: &Value, names: &mut BTreeSet<String>) {
+    match value {
+        Value::String(template) => placeholders(template, names),
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping {
+                match value {
+                    Value::String(selector) if key.as_str() == Some(SELECT_KEY) => {
+                        names.insert(selector.to_owned());
+                    }
+                    value => arguments(value, names),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Flatten a tree of translations into the keys of its messages and the arguments they use
+pub(crate) fn flatten(
+    value: &Value,
+    prefix: &str,
+    messages: &mut BTreeMap<String, BTreeSet<String>>,
+) {
+    match value {
+        Value::Mapping(mapping) if !is_select(mapping) => {
+            for (component, value) in mapping {
+                // The language metadata is not a message
+                if prefix.is_empty() && component.as_str() == Some(META_KEY) {
+                    continue;
+                }
+                if let Some(component) = component.as_str() {
+                    let key = if prefix.is_empty() {
+                        component.to_owned()
+                    } else {
+                        [prefix, component].join(".")
+                    };
+                    flatten(value, &key, messages);
+                }
+            }
+        }
+        Value::String(_) | Value::Mapping(_) => {
+            let mut names = BTreeSet::new();
+            arguments(value, &mut names);
+            messages.insert(prefix.to_owned(), names);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flatten() {
+        let translations = serde_yaml::from_str::<Value>(
+            "meta:\n  name: English\ngreeting: \"Hi {name}, {{not}} {{{ count }}}\"\nposts:\n  $select: count\n  one: \"{count} post by {author}\"\n  other:\n    $select: author\n    other: \"{count} posts\"\n",
+        )
+        .unwrap();
+        let mut messages = BTreeMap::new();
+        flatten(&translations, "", &mut messages);
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        assert_eq!(
+            messages,
+            BTreeMap::from([
+                ("greeting".to_owned(), names(&["name", "count"])),
+                ("posts".to_owned(), names(&["count", "author"])),
+            ])
+        );
+    }
+}
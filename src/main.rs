@@ -1,31 +1,63 @@
-use std::{env, path, sync::OnceLock};
+use std::{
+    path::PathBuf,
+    process,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
 
 use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use clap::Parser as _;
 use db::setup_schema;
-use poise::{Framework, FrameworkError};
-use sea_orm::{ConnectOptions, Database};
-use serenity::all::{ClientBuilder, GatewayIntents};
-use tokio::fs;
+use poise::{CreateReply, Framework, FrameworkError};
+use sea_orm_migration::MigratorTrait as _;
+use serenity::all::{ClientBuilder, FullEvent, GatewayIntents, GuildId, Http};
+use tokio::{
+    fs,
+    io::{self, AsyncWriteExt as _},
+};
 use tracing::{Level, event};
 use tracing_subscriber::prelude::*;
 
+use crate::cli::{Cli, CliCommand, MigrateAction};
 use crate::settings::get_context_settings;
+use crate::shutdown::Shutdown;
 use crate::utils::expect_log::ExpectLog as _;
 
+mod backup;
+mod cli;
 mod commands;
+mod config;
 mod db;
 mod localization;
+mod privacy;
+mod repository;
 mod settings;
+mod shutdown;
 mod utils;
 
-/// Whether the bot is in development mode
-pub(crate) static DEVELOPMENT_MODE: OnceLock<bool> = OnceLock::new();
+/// When the bot was started
+pub(crate) static START_TIME: OnceLock<DateTime<Utc>> = OnceLock::new();
 
 async fn error_handler(
     error: FrameworkError<'_, commands::Data, commands::Error>,
 ) -> anyhow::Result<()> {
+    // The command is done, even though it failed
+    if let Some(ctx) = error.ctx() {
+        ctx.data().shutdown.finish_command(ctx.id());
+    }
+
     // Match the error type
     match error {
+        FrameworkError::CommandCheckFailed {
+            error: None, ctx, ..
+        } if ctx.data().shutdown.is_stopping() => {
+            event!(
+                Level::DEBUG,
+                "Refused command `{}` while shutting down",
+                ctx.command().name
+            );
+        }
         FrameworkError::Command { error, ctx, .. } => {
             event!(
                 Level::ERROR,
@@ -33,14 +65,14 @@ async fn error_handler(
                 ctx.command().name,
                 error
             );
-            let context_settings = get_context_settings(&ctx, &ctx.data().db)
+            let context_settings = get_context_settings(&ctx)
                 .await
                 .context("Failed to get context settings")?;
             ctx.say(
                 localize_message!(
                     "error.command.result.response",
                     &context_settings.language,
-                    error.to_string()
+                    error = error.to_string()
                 )
                 .await
                 .context("Failed to localize message")?,
@@ -56,14 +88,14 @@ async fn error_handler(
                 ctx.command().name,
                 &payload,
             );
-            let context_settings = get_context_settings(&ctx, &ctx.data().db)
+            let context_settings = get_context_settings(&ctx)
                 .await
                 .context("Failed to get context settings")?;
             ctx.say(
                 localize_message!(
                     "error.command.panic.response",
                     &context_settings.language,
-                    &payload
+                    payload = &payload
                 )
                 .await
                 .context("Failed to localize message")?,
@@ -80,48 +112,264 @@ async fn error_handler(
     Ok(())
 }
 
-/// The main function
-#[tokio::main]
-async fn main() {
-    // Start tracing
-    let tracing_filter = tracing_subscriber::filter::LevelFilter::from_level(Level::INFO);
-    let (tracing_filter, reload_handle) = tracing_subscriber::reload::Layer::new(tracing_filter);
-    tracing_subscriber::registry()
-        .with(tracing_filter)
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-    event!(Level::INFO, "Starting bot");
-
-    // Check if bot is in development
-    if fs::try_exists(path::Path::new("dev.txt"))
+/// Refuse to start commands while the bot is shutting down
+async fn command_check(ctx: commands::Context<'_>) -> anyhow::Result<bool> {
+    if !ctx.data().shutdown.is_stopping() {
+        return Ok(true);
+    }
+    let context_settings = get_context_settings(&ctx)
         .await
-        .expect_log("Could not determine if bot is in development")
+        .context("Failed to get context settings")?;
+    ctx.send(
+        CreateReply::default()
+            .content(
+                localize_message!(
+                    "error.command.shutting_down.response",
+                    &context_settings.language
+                )
+                .await
+                .context("Failed to localize message")?,
+            )
+            .ephemeral(true),
+    )
+    .await
+    .context("Failed to send message")?;
+    Ok(false)
+}
+
+/// Handle the Discord events that are not commands
+async fn event_handler(event: &FullEvent, data: &commands::Data) -> anyhow::Result<()> {
+    // Let the event finish before shutting down
+    let _work = data.shutdown.track();
+
+    // Forget about guilds the bot was removed from, but not ones that are only unavailable
+    if let FullEvent::GuildDelete { incomplete, .. } = event
+        && !incomplete.unavailable
     {
-        event!(Level::INFO, "Changing bot to development mode");
-        DEVELOPMENT_MODE
-            .set(true)
-            .expect_log("Failed to set development mode");
-        reload_handle
-            .modify(|filter| {
-                *filter = tracing_subscriber::filter::LevelFilter::from_level(Level::DEBUG)
-            })
-            .expect_log("Failed to change log level");
+        let count = privacy::delete_guild_data(data, incomplete.id)
+            .await
+            .context("Failed to delete guild data")?;
+        event!(
+            Level::INFO,
+            "Left guild {}, so deleted its settings and {} quotes",
+            incomplete.id,
+            count
+        );
+    }
+
+    // Return ok
+    Ok(())
+}
+
+/// Check the translations and exit with the result
+fn check_translations(sources: Option<PathBuf>) -> ! {
+    // Only check the source when there is one, which is not the case in the Docker image
+    let sources = sources.or_else(|| {
+        let default = PathBuf::from("src");
+        if default.is_dir() {
+            Some(default)
+        } else {
+            event!(
+                Level::INFO,
+                "No source directory found, skipping the check of the keys used in the source"
+            );
+            None
+        }
+    });
+    let issues = localization::check::check_translations(
+        &localization::translations_dir(),
+        sources.as_deref(),
+    )
+    .expect_log("Failed to check translations");
+    for issue in &issues {
+        println!("{}", issue);
+    }
+    if issues.is_empty() {
+        event!(Level::INFO, "Translations are complete");
+        process::exit(0);
+    } else {
+        event!(Level::ERROR, "Found {} translation issues", issues.len());
+        process::exit(1);
+    }
+}
+
+/// Run or inspect the database migrations and exit
+async fn migrate(action: MigrateAction) -> ! {
+    let db = db::connect()
+        .await
+        .expect_log("Failed to connect to database");
+    match action {
+        MigrateAction::Status => {
+            let applied = db::Migrator::get_applied_migrations(&db)
+                .await
+                .expect_log("Failed to get applied migrations");
+            let pending = db::Migrator::get_pending_migrations(&db)
+                .await
+                .expect_log("Failed to get pending migrations");
+            for migration in &applied {
+                println!("applied  {}", migration.name());
+            }
+            for migration in &pending {
+                println!("pending  {}", migration.name());
+            }
+        }
+        MigrateAction::Up { steps } => {
+            db::Migrator::up(&db, steps)
+                .await
+                .expect_log("Failed to apply migrations");
+            event!(Level::INFO, "Applied pending migrations");
+        }
+        MigrateAction::Down { steps } => {
+            db::Migrator::down(&db, Some(steps.unwrap_or(1)))
+                .await
+                .expect_log("Failed to roll back migrations");
+            event!(Level::INFO, "Rolled back migrations");
+        }
     }
+    process::exit(0);
+}
 
-    // Get the path to the token file
-    let token_path = env::var_os("TOKEN_PATH").unwrap_or("token.txt".into());
+/// Register or clear the slash commands in guilds and globally, then exit
+async fn register_commands(
+    config: &config::Config,
+    mut guilds: Vec<GuildId>,
+    mut global: bool,
+    clear: bool,
+) -> ! {
+    // Use the same places as starting the bot if none were given
+    if guilds.is_empty() && !global {
+        if config.development.enabled {
+            guilds.clone_from(&config.development.guilds);
+        } else {
+            global = true;
+        }
+    }
+
+    // Connect to Discord
+    let token = fs::read_to_string(&config.token_path)
+        .await
+        .expect_log("Could not read token file");
+    let http = Http::new(&token);
+    let application = http
+        .get_current_application_info()
+        .await
+        .expect_log("Failed to get the application");
+    http.set_application_id(application.id);
+
+    // Register or clear the commands
+    let commands = if clear {
+        Vec::new()
+    } else {
+        commands::get_all_commands()
+    };
+    for guild_id in guilds {
+        poise::builtins::register_in_guild(&http, &commands, guild_id)
+            .await
+            .expect_log("Failed to register commands in guild");
+        event!(
+            Level::INFO,
+            "Registered {} commands in guild {}",
+            commands.len(),
+            guild_id
+        );
+    }
+    if global {
+        poise::builtins::register_globally(&http, &commands)
+            .await
+            .expect_log("Failed to register commands globally");
+        event!(
+            Level::INFO,
+            "Registered {} commands globally",
+            commands.len()
+        );
+    }
+    process::exit(0);
+}
+
+/// Write a backup of the database to a file or standard output and exit
+async fn export(file: Option<PathBuf>) -> ! {
+    let db = db::connect()
+        .await
+        .expect_log("Failed to connect to database");
+    setup_schema(&db)
+        .await
+        .expect_log("Failed to setup database schema");
+    let repository = repository::DatabaseRepository::new(db);
+    let backup = backup::create_backup(&repository, &repository)
+        .await
+        .expect_log("Failed to create backup");
+    let json = backup.to_json().expect_log("Failed to write backup");
+    match &file {
+        Some(file) => fs::write(file, json)
+            .await
+            .expect_log("Could not write backup file"),
+        None => {
+            let mut stdout = io::stdout();
+            stdout
+                .write_all(&json)
+                .await
+                .expect_log("Could not write backup");
+            stdout.flush().await.expect_log("Could not write backup");
+        }
+    }
+    event!(
+        Level::INFO,
+        "Exported {} settings entries and {} quotes",
+        backup.settings.len(),
+        backup.quotes.len()
+    );
+    process::exit(0);
+}
+
+/// Load a backup into an empty database and exit
+async fn restore(file: PathBuf) -> ! {
+    let json = fs::read(&file)
+        .await
+        .expect_log("Could not read backup file");
+    let backup = backup::Backup::from_json(&json).expect_log("Could not read backup");
+
+    // Connect to the database and make sure the schema is current
+    let db = db::connect()
+        .await
+        .expect_log("Failed to connect to database");
+    setup_schema(&db)
+        .await
+        .expect_log("Failed to setup database schema");
+
+    // Load the backup
+    if let Err(err) = backup::restore_backup(&db, &backup).await {
+        event!(Level::ERROR, "Failed to restore backup: {:#}", err);
+        process::exit(1);
+    }
+    event!(
+        Level::INFO,
+        "Restored {} settings entries and {} quotes from {}",
+        backup.settings.len(),
+        backup.quotes.len(),
+        file.display()
+    );
+    process::exit(0);
+}
+
+/// Start the bot and run it until it stops
+async fn run(config: &'static config::Config) {
+    event!(Level::INFO, "Starting bot");
+    START_TIME
+        .set(Utc::now())
+        .expect_log("Failed to set start time");
+
+    if config.development.enabled {
+        event!(Level::INFO, "Running in development mode");
+    }
 
     // Get the token
-    let token = fs::read_to_string(token_path)
+    let token = fs::read_to_string(&config.token_path)
         .await
         .expect_log("Could not read token file");
     event!(Level::INFO, "Token loaded");
 
-    // Get the database url
-    let database_url = env::var_os("DATABASE_URL").unwrap_or("sqlite::memory:".into());
-
     // Connect to the database
-    let db = Database::connect(ConnectOptions::new(database_url.to_string_lossy()))
+    let db = db::connect()
         .await
         .expect_log("Failed to connect to database");
 
@@ -130,6 +378,15 @@ async fn main() {
         .await
         .expect_log("Failed to setup database schema");
 
+    // Snapshot the database periodically
+    let shutdown = Arc::new(Shutdown::default());
+    let snapshots = db::spawn_snapshots(db.clone(), shutdown.clone());
+
+    // Reload the translations when they change
+    let _translations_watcher = localization::watch_translations()
+        .inspect_err(|err| event!(Level::WARN, "Not watching translations: {:#}", err))
+        .ok();
+
     // Create the intents
     let intents = GatewayIntents::non_privileged().union(GatewayIntents::MESSAGE_CONTENT);
 
@@ -145,22 +402,38 @@ async fn main() {
                         .expect_log("An additional error occurred in handling the error");
                 })
             },
+            event_handler: |_ctx, event, _framework, data| Box::pin(event_handler(event, data)),
+            command_check: Some(|ctx| Box::pin(command_check(ctx))),
+            pre_command: |ctx| Box::pin(async move { ctx.data().shutdown.start_command(ctx.id()) }),
+            post_command: |ctx| {
+                Box::pin(async move { ctx.data().shutdown.finish_command(ctx.id()) })
+            },
+            owners: config.owners.iter().copied().collect(),
             ..Default::default()
         })
-        .setup(|ctx, _ready, framework| {
-            Box::pin(async move {
-                if *DEVELOPMENT_MODE.get().unwrap_or(&false) {
-                    poise::builtins::register_in_guild(
-                        ctx,
-                        &framework.options().commands,
-                        "1235772100384526377".parse().unwrap(),
-                    )
-                    .await?;
-                } else {
-                    poise::builtins::register_globally(ctx, &framework.options().commands).await?;
-                }
-                Ok(commands::Data::new(db))
-            })
+        .setup({
+            let db = db.clone();
+            let shutdown = shutdown.clone();
+            move |ctx, _ready, framework| {
+                Box::pin(async move {
+                    if config.development.enabled {
+                        for guild_id in &config.development.guilds {
+                            poise::builtins::register_in_guild(
+                                ctx,
+                                &framework.options().commands,
+                                *guild_id,
+                            )
+                            .await?;
+                        }
+                    } else {
+                        poise::builtins::register_globally(ctx, &framework.options().commands)
+                            .await?;
+                    }
+                    let data = commands::Data::new(db, shutdown.clone());
+                    settings::spawn_stats_log(data.settings_cache.clone(), shutdown);
+                    Ok(data)
+                })
+            }
         })
         .build();
 
@@ -170,9 +443,93 @@ async fn main() {
         .await
         .expect_log("Failed to create the client");
 
-    // Start the client
+    // Stop when asked to, after letting the work in progress finish
+    let shutdown_timeout = Duration::from_secs(config.shutdown_timeout_secs);
+    let shard_manager = client.shard_manager.clone();
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            if let Err(err) = shutdown::signal().await {
+                event!(
+                    Level::ERROR,
+                    "Could not listen for shutdown signals: {}",
+                    err
+                );
+                return;
+            }
+            event!(Level::INFO, "Shutting down");
+            shutdown.stop();
+            tokio::select! {
+                finished = shutdown.wait_idle(shutdown_timeout) => {
+                    if !finished {
+                        event!(
+                            Level::WARN,
+                            "Stopping with {} commands and events still running",
+                            shutdown.in_flight()
+                        );
+                    }
+                }
+                _ = shutdown::signal() => event!(Level::WARN, "Forcing shutdown"),
+            }
+            shard_manager.shutdown_all().await;
+        }
+    });
+
+    // Start the client, which returns once the shards are shut down
     client
         .start()
         .await
         .expect_log("Failed to start the client");
+
+    // Let a snapshot being written finish, then close the database
+    shutdown.stop();
+    if let Some(snapshots) = snapshots
+        && tokio::time::timeout(shutdown_timeout, snapshots)
+            .await
+            .is_err()
+    {
+        event!(Level::WARN, "Stopped waiting for the database snapshot");
+    }
+    db.close().await.expect_log("Failed to close the database");
+    event!(Level::INFO, "Stopped");
+}
+
+/// The main function
+#[tokio::main]
+async fn main() {
+    // Read the command line
+    let cli = Cli::parse();
+
+    // Start tracing
+    let tracing_filter = tracing_subscriber::filter::LevelFilter::from_level(Level::INFO);
+    let (tracing_filter, reload_handle) = tracing_subscriber::reload::Layer::new(tracing_filter);
+    tracing_subscriber::registry()
+        .with(tracing_filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .init();
+
+    // Load the configuration and log at its level
+    let config = config::init(cli.config).unwrap_or_else(|err| {
+        event!(Level::ERROR, "Invalid configuration: {:#}", err);
+        process::exit(1);
+    });
+    reload_handle
+        .modify(|filter| {
+            *filter = tracing_subscriber::filter::LevelFilter::from_level(config.log_level.into())
+        })
+        .expect_log("Failed to change log level");
+
+    // Run the command
+    match cli.command.unwrap_or(CliCommand::Run) {
+        CliCommand::Run => run(config).await,
+        CliCommand::Migrate { action } => migrate(action.unwrap_or_default()).await,
+        CliCommand::RegisterCommands {
+            guilds,
+            global,
+            clear,
+        } => register_commands(config, guilds, global, clear).await,
+        CliCommand::CheckTranslations { sources } => check_translations(sources),
+        CliCommand::Export { file } => export(file).await,
+        CliCommand::Restore { file } => restore(file).await,
+    }
 }
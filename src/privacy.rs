@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context as _;
+use chrono::Utc;
+use serde::Serialize;
+use serenity::all::{GuildId, MessageId, UserId};
+
+use crate::{
+    commands::Data,
+    repository::Quote,
+    settings::{SETTINGS, SettingTarget},
+    utils::snowflake_as_string,
+};
+
+/// A quote in an export of a user's data
+#[derive(Debug, Serialize)]
+pub(crate) struct QuoteExport {
+    pub id: i64,
+    #[serde(serialize_with = "snowflake_as_string::serialize")]
+    pub message_id: MessageId,
+    #[serde(serialize_with = "snowflake_as_string::serialize")]
+    pub guild_id: GuildId,
+    pub datetime: String,
+}
+
+impl From<Quote> for QuoteExport {
+    fn from(quote: Quote) -> Self {
+        Self {
+            id: quote.id,
+            message_id: quote.message_id.0,
+            guild_id: quote.guild_id.0,
+            datetime: quote.datetime.and_utc().to_rfc3339(),
+        }
+    }
+}
+
+/// Everything the bot stores about a user
+#[derive(Debug, Serialize)]
+pub(crate) struct UserDataExport {
+    #[serde(serialize_with = "snowflake_as_string::serialize")]
+    pub user_id: UserId,
+    pub exported_at: String,
+    /// The settings the user has set, by key
+    pub settings: BTreeMap<&'static str, String>,
+    /// The quotes of messages the user wrote
+    pub quotes: Vec<QuoteExport>,
+}
+
+impl UserDataExport {
+    /// Write the export as JSON
+    pub(crate) fn to_json(&self) -> Result<Vec<u8>, anyhow::Error> {
+        serde_json::to_vec_pretty(self).context("Failed to write user data as JSON")
+    }
+}
+
+/// Collect everything the bot stores about a user
+pub(crate) async fn export_user_data(
+    data: &Data,
+    user_id: UserId,
+) -> Result<UserDataExport, anyhow::Error> {
+    // Get the user's settings
+    let values = data
+        .settings
+        .get_settings(SettingTarget::User(user_id))
+        .await
+        .context("Could not get user settings")?
+        .unwrap_or_default();
+    let settings = SETTINGS
+        .iter()
+        .filter_map(|setting| {
+            values
+                .get(setting)
+                .map(|value| (setting.key, value.to_owned()))
+        })
+        .collect();
+
+    // Get the quotes of the user's messages
+    let quotes = data
+        .quotes
+        .get_author_quotes(user_id)
+        .await
+        .context("Could not get quotes")?
+        .into_iter()
+        .map(QuoteExport::from)
+        .collect();
+
+    Ok(UserDataExport {
+        user_id,
+        exported_at: Utc::now().to_rfc3339(),
+        settings,
+        quotes,
+    })
+}
+
+/// Delete everything the bot stores about a user, returning how many quotes were deleted
+pub(crate) async fn delete_user_data(data: &Data, user_id: UserId) -> Result<u64, anyhow::Error> {
+    let target = SettingTarget::User(user_id);
+    data.settings
+        .delete_settings(target)
+        .await
+        .context("Could not delete user settings")?;
+    data.settings_cache.invalidate(target);
+    data.quotes
+        .delete_author_quotes(user_id)
+        .await
+        .context("Could not delete quotes")
+}
+
+/// Delete everything the bot stores about a guild, returning how many quotes were deleted
+pub(crate) async fn delete_guild_data(
+    data: &Data,
+    guild_id: GuildId,
+) -> Result<u64, anyhow::Error> {
+    data.settings
+        .delete_guild_settings(guild_id)
+        .await
+        .context("Could not delete guild settings")?;
+    data.settings_cache.invalidate_guild(guild_id);
+    data.quotes
+        .delete_guild_quotes(guild_id)
+        .await
+        .context("Could not delete quotes")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::DateTime;
+    use serenity::all::{ChannelId, MessageId};
+
+    use crate::{repository::MemoryRepository, settings::TIMEZONE};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_export_and_delete_user_data() {
+        let repository = Arc::new(MemoryRepository::default());
+        let data = Data::with_repositories(repository.clone(), repository);
+        let (user_id, other_id) = (UserId::new(1), UserId::new(2));
+        let guild_id = GuildId::new(3);
+        data.settings
+            .set_setting(
+                SettingTarget::User(user_id),
+                &TIMEZONE,
+                Some("Europe/Madrid".to_owned()),
+            )
+            .await
+            .unwrap();
+        data.settings
+            .set_setting(
+                SettingTarget::Channel(guild_id, ChannelId::new(4)),
+                &TIMEZONE,
+                Some("UTC".to_owned()),
+            )
+            .await
+            .unwrap();
+        for (message_id, author_id) in [(10, user_id), (11, other_id)] {
+            data.quotes
+                .add_quote(
+                    MessageId::new(message_id),
+                    guild_id,
+                    author_id,
+                    DateTime::UNIX_EPOCH.naive_utc(),
+                )
+                .await
+                .unwrap();
+        }
+
+        // The export only has the user's data
+        let export = export_user_data(&data, user_id).await.unwrap();
+        assert_eq!(export.settings["timezone"], "Europe/Madrid");
+        assert_eq!(export.quotes.len(), 1);
+        assert_eq!(export.quotes[0].message_id, MessageId::new(10));
+        let json = serde_json::from_slice::<serde_json::Value>(&export.to_json().unwrap()).unwrap();
+        assert_eq!(json["user_id"], "1");
+        assert_eq!(json["quotes"][0]["message_id"], "10");
+
+        // Deleting the user's data leaves other users' data
+        assert_eq!(delete_user_data(&data, user_id).await.unwrap(), 1);
+        let export = export_user_data(&data, user_id).await.unwrap();
+        assert!(export.settings.is_empty() && export.quotes.is_empty());
+        assert_eq!(
+            export_user_data(&data, other_id)
+                .await
+                .unwrap()
+                .quotes
+                .len(),
+            1
+        );
+
+        // Deleting the guild's data removes its quotes and channel settings, cached or not
+        let channel_id = ChannelId::new(4);
+        data.settings_cache
+            .insert_channel(Some(guild_id), channel_id, None);
+        data.settings_cache
+            .insert_channel(Some(GuildId::new(5)), channel_id, None);
+        assert_eq!(delete_guild_data(&data, guild_id).await.unwrap(), 1);
+        assert!(
+            data.settings_cache
+                .channel(Some(guild_id), channel_id)
+                .is_none()
+        );
+        assert!(
+            data.settings_cache
+                .channel(Some(GuildId::new(5)), channel_id)
+                .is_some()
+        );
+        assert!(
+            data.settings
+                .get_settings(SettingTarget::Channel(guild_id, ChannelId::new(4)))
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
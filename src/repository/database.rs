@@ -0,0 +1,166 @@
+use anyhow::Context as _;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sea_orm::DbConn;
+use serenity::all::{GuildId, MessageId, UserId};
+
+use crate::{
+    db::{self, quotebook},
+    settings::{Setting, SettingScope, SettingTarget, SettingValues},
+};
+
+use super::{Quote, QuoteRepository, SettingsRepository};
+
+/// Stores quotes and settings in a database with sea-orm
+#[derive(Debug, Clone)]
+pub(crate) struct DatabaseRepository {
+    db: DbConn,
+}
+
+impl DatabaseRepository {
+    pub(crate) fn new(db: DbConn) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl QuoteRepository for DatabaseRepository {
+    async fn add_quote(
+        &self,
+        message_id: MessageId,
+        guild_id: GuildId,
+        author_id: UserId,
+        datetime: NaiveDateTime,
+    ) -> Result<(), anyhow::Error> {
+        quotebook::create_entry(&self.db, message_id, guild_id, author_id, datetime).await
+    }
+
+    async fn get_quotes(
+        &self,
+        filters: &quotebook::EntryFilters,
+    ) -> Result<Vec<Quote>, anyhow::Error> {
+        quotebook::get_entries(&self.db, filters).await
+    }
+
+    async fn get_author_quotes(&self, author_id: UserId) -> Result<Vec<Quote>, anyhow::Error> {
+        quotebook::get_author_entries(&self.db, author_id).await
+    }
+
+    async fn get_all_quotes(&self) -> Result<Vec<Quote>, anyhow::Error> {
+        quotebook::get_all_entries(&self.db).await
+    }
+
+    async fn delete_author_quotes(&self, author_id: UserId) -> Result<u64, anyhow::Error> {
+        quotebook::delete_author_entries(&self.db, author_id).await
+    }
+
+    async fn delete_guild_quotes(&self, guild_id: GuildId) -> Result<u64, anyhow::Error> {
+        quotebook::delete_guild_entries(&self.db, guild_id).await
+    }
+}
+
+#[async_trait]
+impl SettingsRepository for DatabaseRepository {
+    async fn get_settings(
+        &self,
+        target: SettingTarget,
+    ) -> Result<Option<SettingValues>, anyhow::Error> {
+        // Find the entry of the target
+        let values = match target {
+            SettingTarget::User(user_id) => db::user_settings::get_entry(&self.db, user_id)
+                .await?
+                .map(|model| SettingValues::from_model(&model, SettingScope::User)),
+            SettingTarget::Channel(_, channel_id) => {
+                db::channel_settings::get_entry(&self.db, channel_id)
+                    .await?
+                    .map(|model| SettingValues::from_model(&model, SettingScope::Channel))
+            }
+            SettingTarget::Guild(guild_id) => db::guild_settings::get_entry(&self.db, guild_id)
+                .await?
+                .map(|model| SettingValues::from_model(&model, SettingScope::Guild)),
+        };
+
+        // Return the values
+        Ok(values)
+    }
+
+    async fn get_all_settings(&self) -> Result<Vec<(SettingTarget, SettingValues)>, anyhow::Error> {
+        // Read the entries of every scope
+        let users = db::user_settings::get_all_entries(&self.db)
+            .await?
+            .into_iter()
+            .map(|model| {
+                (
+                    SettingTarget::User(model.user_id.0),
+                    SettingValues::from_model(&model, SettingScope::User),
+                )
+            });
+        let channels = db::channel_settings::get_all_entries(&self.db)
+            .await?
+            .into_iter()
+            .map(|model| {
+                (
+                    SettingTarget::Channel(model.guild_id.0, model.channel_id.0),
+                    SettingValues::from_model(&model, SettingScope::Channel),
+                )
+            });
+        let guilds = db::guild_settings::get_all_entries(&self.db)
+            .await?
+            .into_iter()
+            .map(|model| {
+                (
+                    SettingTarget::Guild(model.guild_id.0),
+                    SettingValues::from_model(&model, SettingScope::Guild),
+                )
+            });
+
+        // Return the values
+        Ok(users.chain(channels).chain(guilds).collect())
+    }
+
+    async fn set_setting(
+        &self,
+        target: SettingTarget,
+        setting: &Setting,
+        value: Option<String>,
+    ) -> Result<(), anyhow::Error> {
+        match target {
+            SettingTarget::User(user_id) => {
+                db::user_settings::update_entry(&self.db, user_id, setting, value)
+                    .await
+                    .context("Failed to update user settings")
+            }
+            SettingTarget::Channel(guild_id, channel_id) => {
+                db::channel_settings::update_entry(&self.db, guild_id, channel_id, setting, value)
+                    .await
+                    .context("Failed to update channel settings")
+            }
+            SettingTarget::Guild(guild_id) => {
+                db::guild_settings::update_entry(&self.db, guild_id, setting, value)
+                    .await
+                    .context("Failed to update guild settings")
+            }
+        }
+    }
+
+    async fn delete_settings(&self, target: SettingTarget) -> Result<(), anyhow::Error> {
+        match target {
+            SettingTarget::User(user_id) => {
+                db::user_settings::delete_entry(&self.db, user_id).await?;
+            }
+            SettingTarget::Channel(_, channel_id) => {
+                db::channel_settings::delete_entry(&self.db, channel_id).await?;
+            }
+            SettingTarget::Guild(guild_id) => {
+                db::guild_settings::delete_entry(&self.db, guild_id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_guild_settings(&self, guild_id: GuildId) -> Result<(), anyhow::Error> {
+        db::channel_settings::delete_guild_entries(&self.db, guild_id).await?;
+        db::guild_settings::delete_entry(&self.db, guild_id).await?;
+        Ok(())
+    }
+}
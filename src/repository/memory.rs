@@ -0,0 +1,200 @@
+use std::{cmp::Reverse, sync::Mutex};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use dashmap::DashMap;
+use serenity::all::{GuildId, MessageId, UserId};
+
+use crate::{
+    db::quotebook,
+    settings::{SETTINGS, Setting, SettingTarget, SettingValues},
+};
+
+use super::{Quote, QuoteRepository, SettingsRepository};
+
+/// Stores quotes and settings in memory, for testing without a database
+#[derive(Debug, Default)]
+pub(crate) struct MemoryRepository {
+    quotes: Mutex<Vec<Quote>>,
+    settings: DashMap<SettingTarget, SettingValues>,
+}
+
+#[async_trait]
+impl QuoteRepository for MemoryRepository {
+    async fn add_quote(
+        &self,
+        message_id: MessageId,
+        guild_id: GuildId,
+        author_id: UserId,
+        datetime: NaiveDateTime,
+    ) -> Result<(), anyhow::Error> {
+        let mut quotes = self.quotes.lock().unwrap();
+        if quotes.iter().any(|quote| quote.message_id.0 == message_id) {
+            return Err(anyhow!("Message {} is already a quote", message_id));
+        }
+        let id = quotes.len() as i64 + 1;
+        quotes.push(Quote {
+            id,
+            message_id: message_id.into(),
+            guild_id: guild_id.into(),
+            author_id: author_id.into(),
+            datetime,
+        });
+        Ok(())
+    }
+
+    async fn get_quotes(
+        &self,
+        filters: &quotebook::EntryFilters,
+    ) -> Result<Vec<Quote>, anyhow::Error> {
+        let mut quotes = self
+            .quotes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|quote| filters.matches(quote))
+            .cloned()
+            .collect::<Vec<_>>();
+        quotes.sort_by_key(|quote| Reverse(quote.message_id));
+        quotes.truncate(filters.max_entries().into());
+        Ok(quotes)
+    }
+
+    async fn get_author_quotes(&self, author_id: UserId) -> Result<Vec<Quote>, anyhow::Error> {
+        let mut quotes = self
+            .quotes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|quote| quote.author_id.0 == author_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        quotes.sort_by_key(|quote| quote.message_id);
+        Ok(quotes)
+    }
+
+    async fn get_all_quotes(&self) -> Result<Vec<Quote>, anyhow::Error> {
+        let mut quotes = self.quotes.lock().unwrap().clone();
+        quotes.sort_by_key(|quote| quote.id);
+        Ok(quotes)
+    }
+
+    async fn delete_author_quotes(&self, author_id: UserId) -> Result<u64, anyhow::Error> {
+        let mut quotes = self.quotes.lock().unwrap();
+        let count = quotes.len();
+        quotes.retain(|quote| quote.author_id.0 != author_id);
+        Ok((count - quotes.len()) as u64)
+    }
+
+    async fn delete_guild_quotes(&self, guild_id: GuildId) -> Result<u64, anyhow::Error> {
+        let mut quotes = self.quotes.lock().unwrap();
+        let count = quotes.len();
+        quotes.retain(|quote| quote.guild_id.0 != guild_id);
+        Ok((count - quotes.len()) as u64)
+    }
+}
+
+#[async_trait]
+impl SettingsRepository for MemoryRepository {
+    async fn get_settings(
+        &self,
+        target: SettingTarget,
+    ) -> Result<Option<SettingValues>, anyhow::Error> {
+        Ok(self.settings.get(&target).map(|values| values.clone()))
+    }
+
+    async fn get_all_settings(&self) -> Result<Vec<(SettingTarget, SettingValues)>, anyhow::Error> {
+        Ok(self
+            .settings
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect())
+    }
+
+    async fn set_setting(
+        &self,
+        target: SettingTarget,
+        setting: &Setting,
+        value: Option<String>,
+    ) -> Result<(), anyhow::Error> {
+        let mut values = self.settings.entry(target).or_default();
+        match value {
+            Some(value) => values.set(setting, value),
+            None => {
+                let mut remaining = SettingValues::new();
+                for other in SETTINGS.iter().filter(|other| other.key != setting.key) {
+                    if let Some(value) = values.get(other) {
+                        remaining.set(other, value.to_owned());
+                    }
+                }
+                *values = remaining;
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_settings(&self, target: SettingTarget) -> Result<(), anyhow::Error> {
+        self.settings.remove(&target);
+        Ok(())
+    }
+
+    async fn delete_guild_settings(&self, guild_id: GuildId) -> Result<(), anyhow::Error> {
+        self.settings.retain(|target, _| match target {
+            SettingTarget::Channel(target_guild_id, _) | SettingTarget::Guild(target_guild_id) => {
+                *target_guild_id != guild_id
+            }
+            SettingTarget::User(_) => true,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_quotes() {
+        let repository = MemoryRepository::default();
+        let guild_id = GuildId::new(1);
+        for (message_id, author_id) in [(10, 1), (30, 2), (20, 1)] {
+            repository
+                .add_quote(
+                    MessageId::new(message_id),
+                    guild_id,
+                    UserId::new(author_id),
+                    DateTime::UNIX_EPOCH.naive_utc(),
+                )
+                .await
+                .unwrap();
+        }
+        assert!(
+            repository
+                .add_quote(
+                    MessageId::new(10),
+                    guild_id,
+                    UserId::new(1),
+                    DateTime::UNIX_EPOCH.naive_utc()
+                )
+                .await
+                .is_err()
+        );
+
+        let filters = quotebook::EntryFilters::new()
+            .guild_id(guild_id)
+            .author_id(UserId::new(1));
+        let quotes = repository.get_quotes(&filters).await.unwrap();
+        let message_ids = quotes
+            .iter()
+            .map(|quote| quote.message_id.0.get())
+            .collect::<Vec<_>>();
+        assert_eq!(message_ids, vec![20, 10]);
+
+        let filters = quotebook::EntryFilters::new().guild_id(guild_id).limit(1);
+        let quotes = repository.get_quotes(&filters).await.unwrap();
+        assert_eq!(quotes[0].message_id.0.get(), 30);
+    }
+}
@@ -0,0 +1,79 @@
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use serenity::all::{GuildId, MessageId, UserId};
+
+use crate::{
+    db::quotebook,
+    settings::{Setting, SettingTarget, SettingValues},
+};
+
+pub(crate) use database::DatabaseRepository;
+#[cfg(test)]
+pub(crate) use memory::MemoryRepository;
+
+mod database;
+#[cfg(test)]
+mod memory;
+
+/// A quote saved in a guild
+pub(crate) type Quote = quotebook::Model;
+
+/// Stores the quotes saved in guilds
+#[async_trait]
+pub(crate) trait QuoteRepository: Debug + Send + Sync {
+    /// Save a message as a quote
+    async fn add_quote(
+        &self,
+        message_id: MessageId,
+        guild_id: GuildId,
+        author_id: UserId,
+        datetime: NaiveDateTime,
+    ) -> Result<(), anyhow::Error>;
+
+    /// Get the newest quotes matching filters
+    async fn get_quotes(
+        &self,
+        filters: &quotebook::EntryFilters,
+    ) -> Result<Vec<Quote>, anyhow::Error>;
+
+    /// Get every quote of messages from an author, in any guild
+    async fn get_author_quotes(&self, author_id: UserId) -> Result<Vec<Quote>, anyhow::Error>;
+
+    /// Get every quote in every guild, in the order they were saved
+    async fn get_all_quotes(&self) -> Result<Vec<Quote>, anyhow::Error>;
+
+    /// Delete every quote of messages from an author, returning how many were deleted
+    async fn delete_author_quotes(&self, author_id: UserId) -> Result<u64, anyhow::Error>;
+
+    /// Delete every quote saved in a guild, returning how many were deleted
+    async fn delete_guild_quotes(&self, guild_id: GuildId) -> Result<u64, anyhow::Error>;
+}
+
+/// Stores the settings set for users, channels and guilds
+#[async_trait]
+pub(crate) trait SettingsRepository: Debug + Send + Sync {
+    /// Get the values of the settings set for a target, if it has any
+    async fn get_settings(
+        &self,
+        target: SettingTarget,
+    ) -> Result<Option<SettingValues>, anyhow::Error>;
+
+    /// Get the values of the settings set for every target
+    async fn get_all_settings(&self) -> Result<Vec<(SettingTarget, SettingValues)>, anyhow::Error>;
+
+    /// Set or clear the value of a setting for a target
+    async fn set_setting(
+        &self,
+        target: SettingTarget,
+        setting: &Setting,
+        value: Option<String>,
+    ) -> Result<(), anyhow::Error>;
+
+    /// Delete the settings set for a target
+    async fn delete_settings(&self, target: SettingTarget) -> Result<(), anyhow::Error>;
+
+    /// Delete the settings set for a guild and every channel in it
+    async fn delete_guild_settings(&self, guild_id: GuildId) -> Result<(), anyhow::Error>;
+}
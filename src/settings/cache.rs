@@ -0,0 +1,308 @@
+use std::{
+    hash::Hash,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use serenity::all::{ChannelId, GuildId, UserId};
+use tokio::task::JoinHandle;
+use tracing::{Level, event};
+
+use super::{ChannelSettings, GuildSettings, SettingTarget, UserSettings};
+use crate::shutdown::Shutdown;
+
+/// How long cached settings are kept by default
+pub(crate) const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+/// How often the cache stats are logged
+const STATS_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How many entries a map can hold before expired entries are purged
+const PURGE_THRESHOLD: usize = 1024;
+
+/// A cached value along with when it was cached
+#[derive(Debug, Clone)]
+struct CacheEntry<V> {
+    value: V,
+    inserted: Instant,
+}
+
+/// A map of cached values that expire after a time
+#[derive(Debug)]
+struct ExpiringMap<K, V>
+where
+    K: Eq + Hash,
+{
+    entries: DashMap<K, CacheEntry<V>>,
+}
+
+impl<K, V> ExpiringMap<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    fn new() -> Self {
+        Self {
+            entries: DashMap::new(),
+        }
+    }
+
+    /// Get a value if it is cached and has not expired
+    fn get(&self, key: &K, ttl: Duration) -> Option<V> {
+        let entry = self.entries.get(key)?;
+        if entry.inserted.elapsed() < ttl {
+            Some(entry.value.clone())
+        } else {
+            drop(entry);
+            self.entries.remove(key);
+            None
+        }
+    }
+
+    /// Cache a value
+    fn insert(&self, key: K, value: V, ttl: Duration) {
+        if self.entries.len() >= PURGE_THRESHOLD {
+            self.entries
+                .retain(|_, entry| entry.inserted.elapsed() < ttl);
+        }
+        self.entries.insert(
+            key,
+            CacheEntry {
+                value,
+                inserted: Instant::now(),
+            },
+        );
+    }
+
+    /// Remove a value from the cache
+    fn remove(&self, key: &K) {
+        self.entries.remove(key);
+    }
+
+    /// Remove the values whose keys match a condition
+    fn remove_where(&self, condition: impl Fn(&K) -> bool) {
+        self.entries.retain(|key, _| !condition(key));
+    }
+}
+
+/// Counts of cache lookups
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Get the fraction of lookups that were hits
+    pub(crate) fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// An in-memory cache of the settings stored in the database
+#[derive(Debug)]
+pub(crate) struct SettingsCache {
+    ttl: Duration,
+    users: ExpiringMap<UserId, UserSettings>,
+    /// The channels, along with the guild they are in if any
+    channels: ExpiringMap<(Option<GuildId>, ChannelId), Option<ChannelSettings>>,
+    guilds: ExpiringMap<GuildId, Option<GuildSettings>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl SettingsCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            users: ExpiringMap::new(),
+            channels: ExpiringMap::new(),
+            guilds: ExpiringMap::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Count a lookup in the stats
+    fn record<V>(&self, value: Option<V>) -> Option<V> {
+        if value.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        value
+    }
+
+    /// Get the counts of cache lookups
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Get the cached settings of a user
+    pub(crate) fn user(&self, user_id: UserId) -> Option<UserSettings> {
+        self.record(self.users.get(&user_id, self.ttl))
+    }
+
+    /// Cache the settings of a user
+    pub(crate) fn insert_user(&self, user_id: UserId, settings: UserSettings) {
+        self.users.insert(user_id, settings, self.ttl);
+    }
+
+    /// Get the cached settings of a channel
+    pub(crate) fn channel(
+        &self,
+        guild_id: Option<GuildId>,
+        channel_id: ChannelId,
+    ) -> Option<Option<ChannelSettings>> {
+        self.record(self.channels.get(&(guild_id, channel_id), self.ttl))
+    }
+
+    /// Cache the settings of a channel
+    pub(crate) fn insert_channel(
+        &self,
+        guild_id: Option<GuildId>,
+        channel_id: ChannelId,
+        settings: Option<ChannelSettings>,
+    ) {
+        self.channels
+            .insert((guild_id, channel_id), settings, self.ttl);
+    }
+
+    /// Get the cached settings of a guild
+    pub(crate) fn guild(&self, guild_id: GuildId) -> Option<Option<GuildSettings>> {
+        self.record(self.guilds.get(&guild_id, self.ttl))
+    }
+
+    /// Cache the settings of a guild
+    pub(crate) fn insert_guild(&self, guild_id: GuildId, settings: Option<GuildSettings>) {
+        self.guilds.insert(guild_id, settings, self.ttl);
+    }
+
+    /// Remove the cached settings of a target
+    pub(crate) fn invalidate(&self, target: SettingTarget) {
+        match target {
+            SettingTarget::User(user_id) => self.users.remove(&user_id),
+            SettingTarget::Channel(guild_id, channel_id) => {
+                self.channels.remove(&(Some(guild_id), channel_id))
+            }
+            SettingTarget::Guild(guild_id) => self.guilds.remove(&guild_id),
+        }
+    }
+
+    /// Remove the cached settings of a guild and all of its channels
+    pub(crate) fn invalidate_guild(&self, guild_id: GuildId) {
+        self.guilds.remove(&guild_id);
+        self.channels
+            .remove_where(|(channel_guild_id, _)| *channel_guild_id == Some(guild_id));
+    }
+}
+
+impl Default for SettingsCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_TTL)
+    }
+}
+
+/// Periodically log the stats of a settings cache until the bot stops
+pub(crate) fn spawn_stats_log(
+    cache: Arc<SettingsCache>,
+    shutdown: Arc<Shutdown>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(STATS_INTERVAL);
+        // The first tick completes immediately, when nothing has been looked up yet
+        interval.tick().await;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.stopped() => break,
+            }
+            let stats = cache.stats();
+            event!(
+                Level::INFO,
+                "Settings cache had {} hits and {} misses, a hit rate of {:.1}%",
+                stats.hits,
+                stats.misses,
+                stats.hit_rate() * 100.0
+            );
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiring_map() {
+        // Values are kept until they expire
+        let map = ExpiringMap::new();
+        map.insert(1, "one", DEFAULT_TTL);
+        assert_eq!(map.get(&1, DEFAULT_TTL), Some("one"));
+        assert_eq!(map.get(&1, Duration::ZERO), None);
+        assert_eq!(map.entries.len(), 0);
+
+        // Expired values are purged once the map is full
+        for key in 0..PURGE_THRESHOLD {
+            map.insert(key, "old", DEFAULT_TTL);
+        }
+        map.insert(PURGE_THRESHOLD, "new", Duration::ZERO);
+        assert_eq!(map.entries.len(), 1);
+        assert_eq!(map.get(&PURGE_THRESHOLD, DEFAULT_TTL), Some("new"));
+
+        // Values that have not expired are not purged
+        for key in 0..PURGE_THRESHOLD {
+            map.insert(key, "old", DEFAULT_TTL);
+        }
+        map.insert(PURGE_THRESHOLD + 1, "new", DEFAULT_TTL);
+        assert_eq!(map.entries.len(), PURGE_THRESHOLD + 2);
+    }
+
+    #[tokio::test]
+    async fn test_settings_cache() {
+        let cache = SettingsCache::new(Duration::from_millis(50));
+        let (guild_id, other_guild_id) = (GuildId::new(1), GuildId::new(2));
+        let channel_id = ChannelId::new(3);
+        let user_id = UserId::new(4);
+        cache.insert_user(user_id, UserSettings::default());
+        cache.insert_guild(guild_id, None);
+        cache.insert_channel(Some(guild_id), channel_id, None);
+        cache.insert_channel(Some(other_guild_id), channel_id, None);
+        cache.insert_channel(None, channel_id, None);
+
+        // Invalidating a target only removes its settings
+        assert!(cache.user(user_id).is_some());
+        cache.invalidate(SettingTarget::User(user_id));
+        assert!(cache.user(user_id).is_none());
+        cache.invalidate(SettingTarget::Channel(other_guild_id, channel_id));
+        assert!(cache.channel(Some(other_guild_id), channel_id).is_none());
+        assert!(cache.channel(Some(guild_id), channel_id).is_some());
+
+        // Invalidating a guild removes its channels too
+        cache.invalidate_guild(guild_id);
+        assert!(cache.guild(guild_id).is_none());
+        assert!(cache.channel(Some(guild_id), channel_id).is_none());
+        assert!(cache.channel(None, channel_id).is_some());
+
+        // Settings expire after the time to live
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(cache.channel(None, channel_id).is_none());
+
+        // Every lookup is counted
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (3, 5));
+        assert_eq!(stats.hit_rate(), 0.375);
+    }
+}
@@ -0,0 +1,27 @@
+use anyhow::Context as _;
+
+use crate::commands::Context;
+
+use super::{SettingTarget, SettingValues};
+
+/// Represents the settings of a channel
+#[derive(Debug, Clone)]
+pub(crate) struct ChannelSettings {
+    pub values: SettingValues,
+}
+
+/// Get the channel settings
+pub(crate) async fn get_channel_settings(
+    ctx: &Context<'_>,
+) -> Result<Option<ChannelSettings>, anyhow::Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(None);
+    };
+    let values = ctx
+        .data()
+        .settings
+        .get_settings(SettingTarget::Channel(guild_id, ctx.channel_id()))
+        .await
+        .context("Could not get channel settings")?;
+    Ok(values.map(|values| ChannelSettings { values }))
+}
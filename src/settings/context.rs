@@ -1,41 +1,132 @@
 use anyhow::Context as _;
-use sea_orm::prelude::*;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 
-use crate::{commands::Context, localization::Language};
+use crate::{
+    commands::Context,
+    localization::{Argument, DateFormat, Language},
+};
 
-use super::{guild, user};
+use super::{
+    DATE_FORMAT, LANGUAGE, SETTINGS, SettingScope, SettingValues, TIMEZONE, channel, guild, user,
+};
 
 /// Represents the settings of a context
 #[derive(Debug, Clone)]
 pub(crate) struct ContextSettings {
     pub language: Language,
+    pub timezone: Tz,
+    pub date_format: DateFormat,
+}
+
+impl ContextSettings {
+    /// Create a message argument showing a date in the timezone and format of the context
+    pub(crate) fn date(&self, datetime: DateTime<Utc>) -> Argument {
+        Argument::Date {
+            datetime,
+            timezone: self.timezone,
+            format: self.date_format,
+        }
+    }
+}
+
+/// Get the language from the locales Discord gives for a context
+fn get_locale_language(ctx: &Context<'_>) -> Language {
+    // Use the locale of the user's client
+    if let Some(language) = ctx.locale().and_then(|locale| locale.parse().ok()) {
+        return language;
+    }
+
+    // Use the preferred locale of the guild
+    if let Some(language) = ctx
+        .guild()
+        .and_then(|guild| guild.preferred_locale.parse().ok())
+    {
+        return language;
+    }
+
+    Language::default()
 }
 
 /// Get the settings for a context
 pub(crate) async fn get_context_settings(
     ctx: &Context<'_>,
-    db: &DbConn,
 ) -> Result<ContextSettings, anyhow::Error> {
+    // Get the settings cache
+    let cache = &ctx.data().settings_cache;
+
     // Get the user's settings
-    let user_settings = user::get_user_settings(ctx, db)
-        .await
-        .context("Could not get user settings from the database")?;
+    let user_id = ctx.author().id;
+    let user_settings = if let Some(user_settings) = cache.user(user_id) {
+        user_settings
+    } else {
+        let user_settings = user::get_user_settings(ctx)
+            .await
+            .context("Could not get user settings from the database")?;
+        cache.insert_user(user_id, user_settings.clone());
+        user_settings
+    };
 
-    // Get the guild's settings
-    let guild_settings = guild::get_guild_settings(ctx, db)
-        .await
-        .context("Could not get guild settings from the database")?;
+    // Get the channel's settings
+    let channel_id = ctx.channel_id();
+    let channel_settings = if let Some(channel_settings) = cache.channel(ctx.guild_id(), channel_id)
+    {
+        channel_settings
+    } else {
+        let channel_settings = channel::get_channel_settings(ctx)
+            .await
+            .context("Could not get channel settings from the database")?;
+        cache.insert_channel(ctx.guild_id(), channel_id, channel_settings.clone());
+        channel_settings
+    };
 
-    // Combine the settings to get the context settings
-    if let Some(guild_settings) = guild_settings {
-        Ok(ContextSettings {
-            language: user_settings
-                .language
-                .unwrap_or(guild_settings.language.unwrap_or_default()),
-        })
+    // Get the guild's settings
+    let guild_settings = if let Some(guild_id) = ctx.guild_id() {
+        if let Some(guild_settings) = cache.guild(guild_id) {
+            guild_settings
+        } else {
+            let guild_settings = guild::get_guild_settings(ctx)
+                .await
+                .context("Could not get guild settings from the database")?;
+            cache.insert_guild(guild_id, guild_settings.clone());
+            guild_settings
+        }
     } else {
-        Ok(ContextSettings {
-            language: user_settings.language.unwrap_or_default(),
-        })
+        None
+    };
+
+    // Resolve each setting from the most specific scope it is set in
+    let mut values = SettingValues::new();
+    for setting in SETTINGS {
+        let value = setting
+            .scopes
+            .iter()
+            .find_map(|scope| match scope {
+                SettingScope::User => user_settings.values.get(setting),
+                SettingScope::Channel => channel_settings
+                    .as_ref()
+                    .and_then(|channel_settings| channel_settings.values.get(setting)),
+                SettingScope::Guild => guild_settings
+                    .as_ref()
+                    .and_then(|guild_settings| guild_settings.values.get(setting)),
+            })
+            .unwrap_or(setting.default);
+        values.set(setting, value);
     }
+
+    // Combine the settings to get the context settings
+    Ok(ContextSettings {
+        language: values
+            .get(&LANGUAGE)
+            .and_then(|language| language.parse().ok())
+            .unwrap_or_else(|| get_locale_language(ctx)),
+        timezone: values
+            .get(&TIMEZONE)
+            .and_then(|timezone| timezone.parse().ok())
+            .unwrap_or(Tz::UTC),
+        date_format: values
+            .get(&DATE_FORMAT)
+            .and_then(DateFormat::from_code)
+            .unwrap_or_default(),
+    })
 }
@@ -1,9 +1,19 @@
 #![allow(unused_imports)]
 
+pub(crate) use cache::{SettingsCache, spawn_stats_log};
+pub(crate) use channel::{ChannelSettings, get_channel_settings};
 pub(crate) use context::{ContextSettings, get_context_settings};
-pub(crate) use guild::GuildSettings;
-pub(crate) use user::UserSettings;
+pub(crate) use guild::{GuildSettings, get_guild_settings};
+pub(crate) use registry::{
+    ChoiceLabel, DATE_FORMAT, LANGUAGE, SETTINGS, Setting, SettingChoice, SettingKind,
+    SettingScope, SettingTarget, SettingValues, TIMEZONE, get_setting, scope_settings,
+    update_setting,
+};
+pub(crate) use user::{UserSettings, get_user_settings};
 
+mod cache;
+mod channel;
 mod context;
 mod guild;
+mod registry;
 mod user;
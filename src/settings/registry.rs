@@ -0,0 +1,376 @@
+use std::{borrow::Cow, collections::HashMap, str::FromStr};
+
+use anyhow::{Context as _, anyhow};
+use chrono_tz::Tz;
+use sea_orm::{Value, prelude::*};
+use serenity::all::{ChannelId, GuildId, UserId};
+
+use crate::{
+    localization::{Language, languages},
+    localize_message,
+    repository::SettingsRepository,
+};
+
+use super::SettingsCache;
+
+/// A scope that a setting can be set in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum SettingScope {
+    User,
+    Channel,
+    Guild,
+}
+
+/// A place that settings can be set in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum SettingTarget {
+    User(UserId),
+    Channel(GuildId, ChannelId),
+    Guild(GuildId),
+}
+
+impl SettingTarget {
+    /// Get the scope of the target
+    pub(crate) fn scope(&self) -> SettingScope {
+        match self {
+            Self::User(_) => SettingScope::User,
+            Self::Channel(_, _) => SettingScope::Channel,
+            Self::Guild(_) => SettingScope::Guild,
+        }
+    }
+}
+
+/// The label of a value that a setting can take
+#[derive(Debug, Clone)]
+pub(crate) enum ChoiceLabel {
+    /// The translation key of the label
+    Key(&'static str),
+    /// A label shown as it is in every language
+    Text(String),
+}
+
+impl ChoiceLabel {
+    /// Get the text of the label in a language
+    pub(crate) async fn localize(&self, language: &Language) -> Result<String, anyhow::Error> {
+        match self {
+            Self::Key(key) => localize_message!(*key, language).await,
+            Self::Text(text) => Ok(text.to_owned()),
+        }
+    }
+}
+
+/// A value that a choice setting can take
+#[derive(Debug, Clone)]
+pub(crate) struct SettingChoice {
+    /// The value stored in the database
+    pub value: Cow<'static, str>,
+    /// The label for the value
+    pub label: ChoiceLabel,
+}
+
+/// The type of the values of a setting
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SettingKind {
+    /// One of a fixed set of values
+    Choice(&'static [SettingChoice]),
+    /// An IANA timezone name
+    Timezone,
+    /// The tag of a language translations are available in, or automatic
+    Language,
+}
+
+/// The declaration of a setting
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Setting {
+    /// The key identifying the setting
+    pub key: &'static str,
+    /// The translation key of the label for the setting
+    pub label: &'static str,
+    /// The scopes the setting can be set in, from most to least specific
+    pub scopes: &'static [SettingScope],
+    /// The type of the values of the setting
+    pub kind: SettingKind,
+    /// The value used when the setting is not set in any scope
+    pub default: &'static str,
+    /// The name of the column the setting is stored in
+    pub column: &'static str,
+}
+
+/// An error from giving a setting an invalid value
+#[derive(Debug, Clone)]
+pub(crate) struct SettingValueError {
+    key: &'static str,
+    value: String,
+}
+
+impl std::fmt::Display for SettingValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid value for setting {}: {}", self.key, self.value)
+    }
+}
+
+impl std::error::Error for SettingValueError {}
+
+impl Setting {
+    /// Check if the setting can be set in a scope
+    pub(crate) fn has_scope(&self, scope: SettingScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// Get the valid values for the setting
+    pub(crate) fn choices(&self) -> Vec<SettingChoice> {
+        match self.kind {
+            SettingKind::Choice(choices) => choices.to_vec(),
+            SettingKind::Timezone => Vec::new(),
+            SettingKind::Language => [AUTO_LANGUAGE]
+                .into_iter()
+                .chain(languages().iter().map(|language| SettingChoice {
+                    value: language.tag().to_owned().into(),
+                    label: ChoiceLabel::Text(language.name().to_owned()),
+                }))
+                .collect(),
+        }
+    }
+
+    /// Check that a value is valid for the setting
+    pub(crate) fn validate(&self, value: impl AsRef<str>) -> Result<(), SettingValueError> {
+        let value = value.as_ref();
+        let valid = match self.kind {
+            SettingKind::Choice(choices) => choices.iter().any(|choice| choice.value == value),
+            SettingKind::Timezone => value.parse::<Tz>().is_ok(),
+            SettingKind::Language => {
+                value == AUTO_LANGUAGE.value
+                    || languages().iter().any(|language| language.tag() == value)
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(SettingValueError {
+                key: self.key,
+                value: value.to_owned(),
+            })
+        }
+    }
+
+    /// Get the label for a value of the setting
+    pub(crate) fn value_label(&self, value: impl AsRef<str>) -> Option<ChoiceLabel> {
+        let value = value.as_ref();
+        self.choices()
+            .into_iter()
+            .find(|choice| choice.value == value)
+            .map(|choice| choice.label)
+    }
+}
+
+/// The value of the language setting that uses the locales Discord gives
+const AUTO_LANGUAGE: SettingChoice = SettingChoice {
+    value: Cow::Borrowed("auto"),
+    label: ChoiceLabel::Key("settings.language.names.auto"),
+};
+
+/// The language setting
+pub(crate) const LANGUAGE: Setting = Setting {
+    key: "language",
+    label: "settings.language.label",
+    scopes: &[
+        SettingScope::User,
+        SettingScope::Channel,
+        SettingScope::Guild,
+    ],
+    kind: SettingKind::Language,
+    default: "auto",
+    column: "language",
+};
+
+/// The timezone setting
+pub(crate) const TIMEZONE: Setting = Setting {
+    key: "timezone",
+    label: "settings.timezone.label",
+    scopes: &[SettingScope::User, SettingScope::Guild],
+    kind: SettingKind::Timezone,
+    default: "UTC",
+    column: "timezone",
+};
+
+/// The date format setting
+pub(crate) const DATE_FORMAT: Setting = Setting {
+    key: "date_format",
+    label: "settings.date_format.label",
+    scopes: &[SettingScope::User, SettingScope::Guild],
+    kind: SettingKind::Choice(&[
+        SettingChoice {
+            value: Cow::Borrowed("auto"),
+            label: ChoiceLabel::Key("settings.date_format.names.auto"),
+        },
+        SettingChoice {
+            value: Cow::Borrowed("iso"),
+            label: ChoiceLabel::Key("settings.date_format.names.iso"),
+        },
+        SettingChoice {
+            value: Cow::Borrowed("mdy"),
+            label: ChoiceLabel::Key("settings.date_format.names.mdy"),
+        },
+        SettingChoice {
+            value: Cow::Borrowed("dmy"),
+            label: ChoiceLabel::Key("settings.date_format.names.dmy"),
+        },
+    ]),
+    default: "auto",
+    column: "date_format",
+};
+
+/// Every setting
+pub(crate) const SETTINGS: &[Setting] = &[LANGUAGE, TIMEZONE, DATE_FORMAT];
+
+/// Get a setting by its key
+pub(crate) fn get_setting(key: impl AsRef<str>) -> Option<&'static Setting> {
+    let key = key.as_ref();
+    SETTINGS.iter().find(|setting| setting.key == key)
+}
+
+/// Get the settings that can be set in a scope
+pub(crate) fn scope_settings(scope: SettingScope) -> impl Iterator<Item = &'static Setting> {
+    SETTINGS
+        .iter()
+        .filter(move |setting| setting.has_scope(scope))
+}
+
+/// The values of the settings set in a scope
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SettingValues {
+    values: HashMap<&'static str, String>,
+}
+
+impl SettingValues {
+    /// Create an empty set of values
+    pub(crate) fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+        }
+    }
+
+    /// Get the value of a setting
+    pub(crate) fn get(&self, setting: &Setting) -> Option<&str> {
+        self.values.get(setting.key).map(String::as_str)
+    }
+
+    /// Set the value of a setting
+    pub(crate) fn set(&mut self, setting: &Setting, value: impl Into<String>) {
+        self.values.insert(setting.key, value.into());
+    }
+
+    /// Read the values of the settings in a scope from a database model
+    pub(crate) fn from_model<M>(model: &M, scope: SettingScope) -> Self
+    where
+        M: ModelTrait,
+        <M::Entity as EntityTrait>::Column: FromStr,
+    {
+        let mut values = Self::new();
+        for setting in scope_settings(scope) {
+            if let Ok(column) = setting.column.parse()
+                && let Value::String(Some(value)) = model.get(column)
+            {
+                values.set(setting, *value);
+            }
+        }
+        values
+    }
+}
+
+/// Change the value of a setting for a target
+pub(crate) async fn update_setting(
+    repository: &dyn SettingsRepository,
+    cache: &SettingsCache,
+    target: SettingTarget,
+    setting: &Setting,
+    value: Option<String>,
+) -> Result<(), anyhow::Error> {
+    // Make sure the setting can be set for the target
+    if !setting.has_scope(target.scope()) {
+        return Err(anyhow!("Setting {} can not be set here", setting.key))
+            .context("Invalid setting scope");
+    }
+
+    // Make sure the value is valid
+    if let Some(value) = &value {
+        setting.validate(value).context("Invalid setting value")?;
+    }
+
+    // Update the setting
+    let result = repository.set_setting(target, setting, value).await;
+
+    // Make sure the old settings are not used
+    cache.invalidate(target);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{repository::MemoryRepository, settings::UserSettings};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_update_setting() {
+        let repository = MemoryRepository::default();
+        let cache = SettingsCache::default();
+        let user_id = UserId::new(1);
+        let channel = SettingTarget::Channel(GuildId::new(2), ChannelId::new(3));
+
+        // Settings are checked before they are stored
+        assert!(
+            update_setting(&repository, &cache, channel, &TIMEZONE, None)
+                .await
+                .is_err()
+        );
+        assert!(
+            update_setting(
+                &repository,
+                &cache,
+                SettingTarget::User(user_id),
+                &TIMEZONE,
+                Some("Not/A_Timezone".to_owned())
+            )
+            .await
+            .is_err()
+        );
+
+        // Valid settings are stored and the cached settings are dropped
+        cache.insert_user(user_id, UserSettings::default());
+        update_setting(
+            &repository,
+            &cache,
+            SettingTarget::User(user_id),
+            &TIMEZONE,
+            Some("Europe/Madrid".to_owned()),
+        )
+        .await
+        .unwrap();
+        assert!(cache.user(user_id).is_none());
+        let values = repository
+            .get_settings(SettingTarget::User(user_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(values.get(&TIMEZONE), Some("Europe/Madrid"));
+
+        // Removing a setting makes it inherited again
+        update_setting(
+            &repository,
+            &cache,
+            SettingTarget::User(user_id),
+            &TIMEZONE,
+            None,
+        )
+        .await
+        .unwrap();
+        let values = repository
+            .get_settings(SettingTarget::User(user_id))
+            .await
+            .unwrap()
+            .unwrap_or_default();
+        assert_eq!(values.get(&TIMEZONE), None);
+    }
+}
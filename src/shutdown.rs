@@ -0,0 +1,147 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use dashmap::DashSet;
+use tokio::sync::Notify;
+
+/// Tracks the work in progress so the bot can stop without cutting it off
+#[derive(Debug, Default)]
+pub(crate) struct Shutdown {
+    stopping: AtomicBool,
+    /// The ids of the commands being run
+    commands: DashSet<u64>,
+    /// How many commands and events are being handled
+    in_flight: AtomicUsize,
+    /// Notified when the bot starts stopping and when the last work finishes
+    changed: Notify,
+}
+
+/// Marks an event as being handled until it is dropped
+pub(crate) struct WorkGuard<'a>(&'a Shutdown);
+
+impl Drop for WorkGuard<'_> {
+    fn drop(&mut self) {
+        self.0.finish();
+    }
+}
+
+impl Shutdown {
+    /// Check if the bot is stopping, so no new work should be started
+    pub(crate) fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
+
+    /// Stop accepting new work
+    pub(crate) fn stop(&self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        self.changed.notify_waiters();
+    }
+
+    /// Wait until the bot starts stopping
+    pub(crate) async fn stopped(&self) {
+        loop {
+            let changed = self.changed.notified();
+            if self.is_stopping() {
+                return;
+            }
+            changed.await;
+        }
+    }
+
+    /// Get how many commands and events are being handled
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    fn finish(&self) {
+        if self.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.changed.notify_waiters();
+        }
+    }
+
+    /// Mark a command as being run
+    pub(crate) fn start_command(&self, id: u64) {
+        if self.commands.insert(id) {
+            self.in_flight.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Mark a command as done, which can be called more than once for the same command
+    pub(crate) fn finish_command(&self, id: u64) {
+        if self.commands.remove(&id).is_some() {
+            self.finish();
+        }
+    }
+
+    /// Mark an event as being handled until the guard is dropped
+    pub(crate) fn track(&self) -> WorkGuard<'_> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        WorkGuard(self)
+    }
+
+    /// Wait for the work in progress to finish, returning whether it did before the timeout
+    pub(crate) async fn wait_idle(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let changed = self.changed.notified();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                changed.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+}
+
+/// Wait for a signal to stop, which is Ctrl+C or SIGTERM from `docker stop`
+pub(crate) async fn signal() -> Result<(), std::io::Error> {
+    #[cfg(unix)]
+    {
+        let mut terminate =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_for_work() {
+        let shutdown = Arc::new(Shutdown::default());
+        shutdown.start_command(1);
+        shutdown.start_command(1);
+        let guard = shutdown.track();
+        assert_eq!(shutdown.in_flight(), 2);
+
+        // Work that does not finish times out
+        shutdown.stop();
+        shutdown.stopped().await;
+        assert!(!shutdown.wait_idle(Duration::from_millis(10)).await);
+
+        // Finishing the work in another task wakes the waiter
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.wait_idle(Duration::from_secs(5)).await }
+        });
+        drop(guard);
+        shutdown.finish_command(1);
+        shutdown.finish_command(1);
+        assert!(waiter.await.unwrap());
+        assert_eq!(shutdown.in_flight(), 0);
+    }
+}
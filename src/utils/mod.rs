@@ -1,5 +1,6 @@
 pub mod chunked_messages;
 pub mod expect_log;
+pub mod snowflake_as_string;
 
 #[allow(unused)]
 pub mod prelude {
@@ -0,0 +1,67 @@
+//! Serde helpers that write Discord ids as strings, for `#[serde(with = "...")]`
+//!
+//! Ids do not fit in the numbers of most JSON readers, like JavaScript's, so files other
+//! programs read write them as strings. Numbers are still read, for files written by hand.
+
+use std::{fmt::Display, num::NonZeroU64};
+
+use serde::{Deserialize, Deserializer, Serializer, de::Error as _};
+
+/// An id as it can be written in a file
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawId {
+    String(String),
+    Number(u64),
+}
+
+/// Write an id as a string
+pub(crate) fn serialize<T, S>(id: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    serializer.collect_str(id)
+}
+
+/// Read an id from a string or a number
+pub(crate) fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: From<NonZeroU64>,
+    D: Deserializer<'de>,
+{
+    let id = match RawId::deserialize(deserializer)? {
+        RawId::String(id) => id.parse::<NonZeroU64>().map_err(D::Error::custom)?,
+        RawId::Number(id) => NonZeroU64::new(id).ok_or(D::Error::custom("id can not be 0"))?,
+    };
+    Ok(id.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serenity::all::GuildId;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Guild {
+        #[serde(with = "super")]
+        id: GuildId,
+    }
+
+    #[test]
+    fn test_snowflake_as_string() {
+        let guild = Guild {
+            id: GuildId::new(1235772100384526377),
+        };
+        let json = serde_json::to_string(&guild).unwrap();
+        assert_eq!(json, r#"{"id":"1235772100384526377"}"#);
+        assert_eq!(serde_json::from_str::<Guild>(&json).unwrap(), guild);
+        assert_eq!(
+            serde_json::from_str::<Guild>(r#"{"id":1235772100384526377}"#).unwrap(),
+            guild
+        );
+        assert!(serde_json::from_str::<Guild>(r#"{"id":"0"}"#).is_err());
+    }
+}